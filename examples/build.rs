@@ -7,8 +7,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!(
         "Parsed: {:#?}",
         gi::get_build(
-            args.get(1).expect("Missing username"),
-            args.get(2).expect("Missing hoyo hash"),
+            &args
+                .get(1)
+                .expect("Missing username")
+                .parse::<gi::api::Username>()
+                .expect("Invalid username"),
+            &args
+                .get(2)
+                .expect("Missing hoyo hash")
+                .parse::<gi::api::HoyoHash>()
+                .expect("Invalid hoyo hash"),
             args.get(3)
                 .expect("Missing build id")
                 .parse::<u64>()
@@ -9,7 +9,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         gi::get_player(
             args.get(1)
                 .expect("Missing UID")
-                .parse::<u64>()
+                .parse::<gi::api::Uid>()
                 .expect("Invalid UID"),
             args.get(2)
                 .map(|a| a
@@ -0,0 +1,20 @@
+use enka_rs as lib;
+use lib::{EnkaClient, gi::api::Uid};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = std::env::args().collect::<Vec<_>>();
+    println!(
+        "Parsed: {:#?}",
+        EnkaClient::default()
+            .gi()
+            .get_player_full(
+                args.get(1)
+                    .expect("Missing UID")
+                    .parse::<Uid>()
+                    .expect("Invalid UID")
+            )
+            .await?
+    );
+    Ok(())
+}
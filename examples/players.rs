@@ -0,0 +1,25 @@
+use enka_rs as lib;
+use futures_util::StreamExt;
+use lib::{
+    EnkaClient,
+    gi::{BulkOptions, api::Uid},
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let uids = std::env::args()
+        .skip(1)
+        .map(|a| a.parse::<Uid>().expect("Invalid UID"))
+        .collect::<Vec<_>>();
+    let mut players = EnkaClient::default().gi().get_players(
+        uids,
+        BulkOptions {
+            info_only: true,
+            ..Default::default()
+        },
+    );
+    while let Some((uid, player)) = players.next().await {
+        println!("{uid}: {player:#?}");
+    }
+    Ok(())
+}
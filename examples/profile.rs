@@ -6,9 +6,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = std::env::args().collect::<Vec<_>>();
     println!(
         "Parsed: {:#?}",
-        gi::get_profile(args.get(1).expect("Missing username"), None, None)
-            .await
-            .unwrap()
+        gi::get_profile(
+            &args
+                .get(1)
+                .expect("Missing username")
+                .parse::<gi::api::Username>()
+                .expect("Invalid username"),
+            None,
+            None
+        )
+        .await
+        .unwrap()
     );
     Ok(())
 }
@@ -1,30 +1,27 @@
 use enka_rs as lib;
-use lib::{Wrapper, gi::api::profile::hoyo::Hoyo};
+use lib::{Config, EnkaClient, gi::api::Username};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = std::env::args().collect::<Vec<_>>();
-    let username = args.get(1).expect("Missing username");
-    let wrapper = Wrapper {
-        user_agent: None,
+    let username = &args.get(1).expect("Missing username").parse::<Username>()?;
+    let client = EnkaClient::new(Config {
         req_client: Some(reqwest::Client::new()),
-    };
+        ..Default::default()
+    });
+    let gi = client.gi();
     println!(
         "Parsed: {:#?}",
-        wrapper
-            .gi()
-            .get_builds(
-                username,
-                wrapper
-                    .gi()
-                    .get_hoyos(username)
-                    .await?
-                    .iter()
-                    .find(|(_, v)| matches!(v, Hoyo::Genshin(_)))
-                    .ok_or("No hoyos found")?
-                    .0,
-            )
-            .await?
+        gi.get_builds(
+            username,
+            &gi.get_hoyos(username)
+                .await?
+                .genshin()
+                .next()
+                .ok_or("No hoyos found")?
+                .hash,
+        )
+        .await?
     );
     Ok(())
 }
@@ -0,0 +1,430 @@
+//! Command-line access to every enka.network endpoint
+use clap::{Parser, Subcommand, ValueEnum};
+use enka_rs::gi::{
+    self, Route,
+    api::{
+        HoyoHash, Uid, Username,
+        player::info::Info,
+        profile::hoyo::{Hoyo, build::Build},
+    },
+    export::tabular::{Column, Table},
+    store::{Characters, Localizations},
+};
+use reqwest::header::HeaderValue;
+use std::{io::Write, path::PathBuf, process::ExitCode};
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  Success
+  1  Local failure (store or output)
+  2  Invalid arguments
+  3  enka.network could not be reached
+  4  enka.network returned an error (unknown player, rate limit, maintenance...)
+  5  enka.network returned an unexpected response";
+
+/// Query the enka.network API for Genshin Impact showcases, profiles and builds
+#[derive(Parser)]
+#[command(version, about, after_help = EXIT_CODES)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    #[arg(short, long, value_enum, default_value_t = Output::Pretty, global = true)]
+    output: Output,
+    /// Directory holding `characters.json` and `loc.json` from enka.network's store, used to
+    /// display names instead of IDs
+    #[arg(long, env = "ENKA_STORE", global = true)]
+    store: Option<PathBuf>,
+    /// Language of the names
+    #[arg(long, default_value = "en", global = true)]
+    lang: String,
+    /// Sent instead of the default `enka-rs/<commit>` user agent
+    #[arg(long, global = true)]
+    user_agent: Option<HeaderValue>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Showcase of a player
+    Player {
+        uid: Uid,
+        /// Skip the showcased characters
+        #[arg(long)]
+        info_only: bool,
+    },
+    /// enka.network profile
+    Profile { username: Username },
+    /// Game accounts linked to a profile
+    Hoyos { username: Username },
+    /// Game account linked to a profile
+    Hoyo { username: Username, hash: HoyoHash },
+    /// Builds saved for a game account
+    Builds { username: Username, hash: HoyoHash },
+    /// Build saved for a game account
+    Build {
+        username: Username,
+        hash: HoyoHash,
+        id: u64,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Output {
+    /// Human readable tables
+    Pretty,
+    /// Response body, indented
+    Json,
+    /// Response body, as received
+    Raw,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum Failure {
+    #[error(transparent)]
+    Api(#[from] gi::Error),
+    #[error("Failed to read store file {0}")]
+    Store(PathBuf, #[source] Box<dyn std::error::Error>),
+    #[error("Failed to write output")]
+    Output(#[from] std::io::Error),
+}
+
+impl Failure {
+    fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            Failure::Api(gi::Error::Request(_)) => 3,
+            Failure::Api(gi::Error::Response(_)) => 4,
+            Failure::Api(gi::Error::Deserialization(_) | gi::Error::Json(_)) => 5,
+            Failure::Store(..) | Failure::Output(_) => 1,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            let mut message = e.to_string();
+            let mut source = std::error::Error::source(&e);
+            while let Some(e) = source {
+                message += &format!(": {e}");
+                source = e.source();
+            }
+            eprintln!("error: {message}");
+            e.exit_code()
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Failure> {
+    let mut out = std::io::stdout().lock();
+    let user_agent = cli.user_agent.clone();
+    if let Output::Json | Output::Raw = cli.output {
+        let text = gi::get_raw(route(&cli.command), user_agent, None).await?;
+        match cli.output {
+            Output::Json => match serde_json::from_str::<serde_json::Value>(&text) {
+                Ok(v) => {
+                    serde_json::to_writer_pretty(&mut out, &v).map_err(std::io::Error::from)?
+                }
+                Err(_) => out.write_all(text.as_bytes())?,
+            },
+            _ => out.write_all(text.as_bytes())?,
+        }
+        writeln!(out)?;
+        return Ok(());
+    }
+    let (characters, localizations) = load_store(cli.store.as_deref())?;
+    let table = |columns: &[Column]| {
+        let mut table = Table::new(columns, &characters, &localizations);
+        table.language = cli.lang.clone();
+        table
+    };
+    match &cli.command {
+        Command::Player { uid, info_only } => {
+            let (info, avatars) = gi::get_player(*uid, *info_only, user_agent, None).await?;
+            print_info(&mut out, &info)?;
+            if let Some(avatars) = avatars {
+                writeln!(out)?;
+                let mut table = table(&AVATAR_COLUMNS);
+                table.push_player(&info, &avatars);
+                print_table(&mut out, &table, Vec::new())?;
+            }
+        }
+        Command::Profile { username } => {
+            let info = gi::get_profile(username, user_agent, None).await?;
+            print_fields(
+                &mut out,
+                [
+                    ("Username", info.username.to_string()),
+                    ("ID", info.id.to_string()),
+                    ("Level", info.profile.level.to_string()),
+                    ("Bio", info.profile.bio),
+                ],
+            )?;
+        }
+        Command::Hoyos { username } => {
+            let hoyos = gi::get_hoyos(username, user_agent, None).await?;
+            let rows = hoyos
+                .iter_ordered()
+                .map(|(hash, hoyo)| {
+                    let nickname = match hoyo {
+                        Hoyo::Genshin(v) => v.player_info.nickname.clone(),
+                        Hoyo::Other(v) => v["player_info"]["nickname"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_owned(),
+                    };
+                    vec![
+                        hash.to_string(),
+                        game(hoyo).to_owned(),
+                        hoyo.uid().map(|v| v.to_string()).unwrap_or_default(),
+                        nickname,
+                        yes_no(hoyo.is_verified()),
+                        yes_no(hoyo.is_public()),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            write_grid(
+                &mut out,
+                &["Hash", "Game", "UID", "Nickname", "Verified", "Public"],
+                &rows,
+            )?;
+        }
+        Command::Hoyo { username, hash } => {
+            match gi::get_hoyo(username, hash, user_agent, None).await? {
+                Hoyo::Genshin(v) => {
+                    print_fields(
+                        &mut out,
+                        [
+                            ("Hash", v.hash.to_string()),
+                            ("Game", "Genshin Impact".to_owned()),
+                            ("Region", format!("{:?}", v.region)),
+                            ("Verified", yes_no(v.verified)),
+                            ("Public", yes_no(v.public)),
+                        ],
+                    )?;
+                    writeln!(out)?;
+                    print_player(&mut out, v.uid, &v.player_info)?;
+                }
+                Hoyo::Other(v) => {
+                    serde_json::to_writer_pretty(&mut out, &v).map_err(std::io::Error::from)?;
+                    writeln!(out)?;
+                }
+            }
+        }
+        Command::Builds { username, hash } => {
+            let builds = gi::get_builds(username, hash, user_agent, None).await?;
+            let builds = builds
+                .iter_by_avatar()
+                .flat_map(|(_, v)| v)
+                .collect::<Vec<_>>();
+            print_builds(&mut out, table(&AVATAR_COLUMNS), &builds)?;
+        }
+        Command::Build { username, hash, id } => {
+            let build = gi::get_build(username, hash, *id, user_agent, None).await?;
+            print_builds(&mut out, table(&AVATAR_COLUMNS), &[&build])?;
+        }
+    }
+    Ok(())
+}
+
+const AVATAR_COLUMNS: [Column; 11] = [
+    Column::Character,
+    Column::Level,
+    Column::Constellation,
+    Column::Talents,
+    Column::Weapon,
+    Column::Refinement,
+    Column::ArtifactSets,
+    Column::CritValue,
+    Column::CritRate,
+    Column::CritDamage,
+    Column::EnergyRecharge,
+];
+
+fn route(command: &Command) -> Route<'_> {
+    match command {
+        Command::Player { uid, info_only } => Route::Player {
+            uid: *uid,
+            info_only: *info_only,
+        },
+        Command::Profile { username } => Route::Profile(username),
+        Command::Hoyos { username } => Route::Hoyos(username),
+        Command::Hoyo { username, hash } => Route::Hoyo(username, hash),
+        Command::Builds { username, hash } => Route::Builds(username, hash),
+        Command::Build { username, hash, id } => Route::Build(username, hash, *id),
+    }
+}
+
+fn load_store(dir: Option<&std::path::Path>) -> Result<(Characters, Localizations), Failure> {
+    fn load<T: serde::de::DeserializeOwned>(path: PathBuf) -> Result<T, Failure> {
+        std::fs::File::open(&path)
+            .map_err(|e| Failure::Store(path.clone(), e.into()))
+            .and_then(|file| {
+                serde_json::from_reader(std::io::BufReader::new(file))
+                    .map_err(|e| Failure::Store(path.clone(), e.into()))
+            })
+    }
+    match dir {
+        Some(dir) => Ok((
+            load(dir.join("characters.json"))?,
+            load(dir.join("loc.json"))?,
+        )),
+        None => Ok(Default::default()),
+    }
+}
+
+fn game(hoyo: &Hoyo) -> &'static str {
+    match hoyo {
+        Hoyo::Genshin(_) => "Genshin Impact",
+        Hoyo::Other(v) => match v["hoyo_type"].as_u64() {
+            Some(1) => "Honkai: Star Rail",
+            Some(2) => "Zenless Zone Zero",
+            _ => "Unknown",
+        },
+    }
+}
+
+fn yes_no(v: bool) -> String {
+    if v { "yes" } else { "no" }.to_owned()
+}
+
+fn print_info(out: &mut impl Write, info: &Info) -> std::io::Result<()> {
+    print_player(out, Some(info.uid), &info.player_info)?;
+    if let Some(owner) = &info.owner {
+        writeln!(out, "{:<20}{}", "Profile", owner.info.username)?;
+    }
+    Ok(())
+}
+
+fn print_player(
+    out: &mut impl Write,
+    uid: Option<Uid>,
+    player: &gi::api::PlayerInfo,
+) -> std::io::Result<()> {
+    let optional = |v: Option<String>| v.unwrap_or_else(|| "-".to_owned());
+    print_fields(
+        out,
+        [
+            ("Nickname", player.nickname.clone()),
+            ("UID", optional(uid.map(|v| v.to_string()))),
+            ("Adventure Rank", player.level.to_string()),
+            (
+                "World Level",
+                optional(player.world_level.map(|v| v.to_string())),
+            ),
+            ("Signature", player.signature.clone().unwrap_or_default()),
+            ("Achievements", player.finish_achievement_num.to_string()),
+            (
+                "Spiral Abyss",
+                optional(player.spiral_abyss().map(|v| v.to_string())),
+            ),
+            (
+                "Imaginarium Theater",
+                optional(player.imaginarium_theater().map(|v| v.to_string())),
+            ),
+            (
+                "Stygian Onslaught",
+                optional(player.stygian_onslaught().map(|v| match v.clear_time {
+                    Some(time) => format!("{} ({}s)", v.difficulty, time.as_secs()),
+                    None => v.difficulty.to_string(),
+                })),
+            ),
+        ],
+    )
+}
+
+fn print_fields<const N: usize>(
+    out: &mut impl Write,
+    fields: [(&str, String); N],
+) -> std::io::Result<()> {
+    for (name, value) in fields {
+        writeln!(out, "{name:<20}{value}")?;
+    }
+    Ok(())
+}
+
+/// Builds table, prefixed by the name and visibility of every build
+fn print_builds(
+    out: &mut impl Write,
+    mut table: Table<'_>,
+    builds: &[&Build],
+) -> std::io::Result<()> {
+    for build in builds {
+        table.push_avatar(&build.avatar_data);
+    }
+    let prefix = builds
+        .iter()
+        .map(|v| {
+            vec![
+                v.id.to_string(),
+                if v.live {
+                    "(live)".to_owned()
+                } else {
+                    v.name.clone()
+                },
+                yes_no(v.public),
+            ]
+        })
+        .collect();
+    print_table(out, &table, prefix)
+}
+
+/// Prints `table`, each row being preceded by the matching row of `prefix` if any
+fn print_table(
+    out: &mut impl Write,
+    table: &Table<'_>,
+    prefix: Vec<Vec<String>>,
+) -> std::io::Result<()> {
+    let prefix_headers = if prefix.is_empty() {
+        &[][..]
+    } else {
+        &["ID", "Build", "Public"][..]
+    };
+    let headers = prefix_headers
+        .iter()
+        .copied()
+        .chain(table.columns().iter().map(Column::header))
+        .collect::<Vec<_>>();
+    let rows = table
+        .rows()
+        .iter()
+        .zip(prefix.into_iter().chain(std::iter::repeat(Vec::new())))
+        .map(|(row, prefix)| {
+            prefix
+                .into_iter()
+                .chain(row.iter().map(ToString::to_string))
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    write_grid(out, &headers, &rows)
+}
+
+fn write_grid(out: &mut impl Write, headers: &[&str], rows: &[Vec<String>]) -> std::io::Result<()> {
+    let width = |v: &str| v.chars().count();
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|v| width(v))
+                .chain([width(header)])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+    let line = |out: &mut dyn Write, cells: &mut dyn Iterator<Item = &str>| {
+        let line = cells
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(out, "{}", line.trim_end())
+    };
+    line(out, &mut headers.iter().copied())?;
+    for row in rows {
+        line(out, &mut row.iter().map(String::as_str))?;
+    }
+    Ok(())
+}
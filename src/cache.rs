@@ -0,0 +1,48 @@
+#![cfg_attr(not(all(feature = "gi", feature = "stateful")), allow(dead_code))]
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// In-memory cache of raw response bodies, keyed by endpoint path
+#[derive(Debug)]
+pub(crate) struct Cache {
+    default_ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Arc<str>)>>,
+}
+
+impl Cache {
+    pub(crate) fn new(default_ttl: Duration) -> Self {
+        Cache {
+            default_ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<Arc<str>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|(expires, _)| *expires > Instant::now())
+            .map(|(_, body)| body.clone())
+    }
+
+    /// Stores `body` until the `ttl` it reports expires, falling back to the default ttl for
+    /// responses without one
+    pub(crate) fn insert(&self, key: String, body: Arc<str>) {
+        #[derive(Deserialize)]
+        struct Ttl {
+            ttl: Option<u64>,
+        }
+        let ttl = serde_json::from_str::<Ttl>(&body)
+            .ok()
+            .and_then(|v| v.ttl)
+            .map_or(self.default_ttl, Duration::from_secs);
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (expires, _)| *expires > now);
+        entries.insert(key, (now + ttl, body));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{cache::Cache, metrics::Metrics, rate_limit::RateLimiter};
+use reqwest::{Client, Url, header::HeaderValue};
+use std::{sync::Arc, time::Duration};
+
+/// Options used to build an [`EnkaClient`]
+#[derive(Default)]
+pub struct Config {
+    pub user_agent: Option<HeaderValue>,
+    /// Transport used for every request, a new one is created when missing
+    pub req_client: Option<Client>,
+    /// Observer notified about every request made through the client
+    pub metrics: Option<Arc<dyn Metrics>>,
+    /// Enables the in-memory response cache
+    ///
+    /// Responses are kept for the `ttl` reported by enka.network, or for this duration when the
+    /// response does not report one.
+    pub cache_ttl: Option<Duration>,
+    /// Minimum delay between the start of two requests
+    pub min_request_interval: Option<Duration>,
+    /// Root the endpoint paths are resolved against, `https://enka.network/` when missing, e.g.
+    /// to go through a mirror
+    pub base_url: Option<Url>,
+}
+
+/// Cheaply cloneable client owning the configuration, cache, rate limiter and transport shared by
+/// every game wrapper
+///
+/// Identical requests made concurrently through the same client share a single HTTP request.
+#[derive(Clone, Debug)]
+pub struct EnkaClient(#[cfg_attr(not(feature = "gi"), allow(dead_code))] pub(crate) Arc<Inner>);
+
+pub(crate) struct Inner {
+    pub(crate) user_agent: Option<HeaderValue>,
+    pub(crate) req_client: Client,
+    pub(crate) metrics: Option<Arc<dyn Metrics>>,
+    pub(crate) cache: Option<Cache>,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    pub(crate) base_url: Option<Url>,
+    #[cfg(feature = "gi")]
+    pub(crate) gi_inflight: crate::inflight::Inflight<crate::gi::Result<Arc<str>>>,
+}
+
+impl std::fmt::Debug for Inner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Inner")
+            .field("user_agent", &self.user_agent)
+            .field("req_client", &self.req_client)
+            .field("metrics", &self.metrics.is_some())
+            .field("cache", &self.cache)
+            .field("rate_limiter", &self.rate_limiter)
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for EnkaClient {
+    fn default() -> Self {
+        EnkaClient::new(Config::default())
+    }
+}
+
+impl EnkaClient {
+    pub fn new(config: Config) -> Self {
+        let Config {
+            user_agent,
+            req_client,
+            metrics,
+            cache_ttl,
+            min_request_interval,
+            base_url,
+        } = config;
+        EnkaClient(Arc::new(Inner {
+            user_agent,
+            req_client: req_client.unwrap_or_default(),
+            metrics,
+            cache: cache_ttl.map(Cache::new),
+            rate_limiter: min_request_interval.map(RateLimiter::new),
+            base_url,
+            #[cfg(feature = "gi")]
+            gi_inflight: crate::inflight::Inflight::new(),
+        }))
+    }
+
+    #[cfg(feature = "gi")]
+    pub fn gi(&self) -> crate::gi::Wrapper {
+        crate::gi::Wrapper {
+            client: self.clone(),
+        }
+    }
+
+    #[cfg(feature = "hsr")]
+    pub fn hsr(&self) -> crate::hsr::Wrapper {
+        crate::hsr::Wrapper {
+            client: self.clone(),
+        }
+    }
+
+    #[cfg(feature = "zzz")]
+    pub fn zzz(&self) -> crate::zzz::Wrapper {
+        crate::zzz::Wrapper {
+            client: self.clone(),
+        }
+    }
+
+    #[cfg(feature = "gi")]
+    pub(crate) fn context(&self) -> crate::gi::Context<'_> {
+        let Inner {
+            user_agent,
+            req_client,
+            metrics,
+            cache,
+            rate_limiter,
+            base_url,
+            gi_inflight,
+        } = &*self.0;
+        crate::gi::Context {
+            user_agent: user_agent.as_ref(),
+            req_client: Some(req_client),
+            metrics: metrics.as_deref(),
+            cache: cache.as_ref(),
+            rate_limiter: rate_limiter.as_ref(),
+            base_url: base_url.as_ref(),
+            inflight: Some(gi_inflight),
+        }
+    }
+}
+
+impl From<Config> for EnkaClient {
+    fn from(config: Config) -> Self {
+        EnkaClient::new(config)
+    }
+}
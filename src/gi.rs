@@ -1,275 +1,889 @@
 pub mod api;
+pub mod calc;
+#[cfg(feature = "card")]
+pub mod card;
+pub mod damage;
+pub mod diff;
+pub mod export;
+#[cfg(feature = "history")]
+pub mod history;
+pub mod store;
+use std::sync::Arc;
 pub type Result<T> = std::result::Result<T, Error>;
+/// Player info along with the showcased characters, as returned by [`get_player`]
+pub type Player = (api::player::info::Info, Option<Vec<api::AvatarInfo>>);
 
-#[derive(thiserror::Error, Debug)]
+/// Errors returned by the API endpoint functions
+///
+/// Cloneable so that a single failed request can be reported to every caller sharing it.
+#[derive(thiserror::Error, Debug, Clone)]
 pub enum Error {
     // #[error("Failed to construct request URL")]
     // Url(#[from] url::ParseError),
     #[error("Failed to submit request")]
-    Request(#[from] reqwest::Error),
+    Request(#[source] Arc<reqwest::Error>),
     #[error("enka.network query failed")]
     Response(String),
     #[error("Failed to deserialize response")]
     Deserialization(#[from] serde::de::value::Error),
     #[error("Failed to parse Json")]
-    Json(#[from] serde_json::Error),
+    Json(#[source] Arc<serde_json::Error>),
 }
 
-#[cfg(all(feature = "logging", debug_assertions))]
-macro_rules! log {
-    ($msg:literal $(, $args:expr)*) => {
-        {
-            println!($msg $(, $args)*);
-        }
-    };
-    ($expr:expr) => {{
-        let x = $expr;
-        println!("{x:?}");
-        x
-    }};
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Request(Arc::new(e))
+    }
 }
 
-#[cfg(not(all(feature = "logging", debug_assertions)))]
-macro_rules! log {
-    ($msg:literal $(, $args:expr)*) => {};
-    ($expr:expr) => {
-        $expr
-    };
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(Arc::new(e))
+    }
+}
+
+/// Request to one of the enka.network endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route<'a> {
+    Player { uid: api::Uid, info_only: bool },
+    Profile(&'a api::Username),
+    Hoyos(&'a api::Username),
+    Hoyo(&'a api::Username, &'a api::HoyoHash),
+    Builds(&'a api::Username, &'a api::HoyoHash),
+    Build(&'a api::Username, &'a api::HoyoHash, u64),
+}
+
+impl Route<'_> {
+    /// Path relative to `https://enka.network/`, with usernames and hashes percent-encoded
+    pub fn path(&self) -> String {
+        match self {
+            Route::Player { uid, info_only } => {
+                format!("api/uid/{uid}/{}", if *info_only { "?info" } else { "" })
+            }
+            Route::Profile(username) => {
+                format!("api/profile/{}/?format=json", username.path_segment())
+            }
+            Route::Hoyos(username) => format!("api/profile/{}/hoyos", username.path_segment()),
+            Route::Hoyo(username, hash) => format!(
+                "api/profile/{}/hoyos/{}/?format=json",
+                username.path_segment(),
+                hash.path_segment()
+            ),
+            Route::Builds(username, hash) => format!(
+                "api/profile/{}/hoyos/{}/builds",
+                username.path_segment(),
+                hash.path_segment()
+            ),
+            Route::Build(username, hash, build_id) => format!(
+                "api/profile/{}/hoyos/{}/builds/{build_id}",
+                username.path_segment(),
+                hash.path_segment()
+            ),
+        }
+    }
+
+    /// Family the route is reported under by [`crate::metrics::Metrics`]
+    pub fn family(&self) -> crate::metrics::Endpoint {
+        use crate::metrics::Endpoint;
+        match self {
+            Route::Player { .. } => Endpoint::Uid,
+            Route::Profile(_) => Endpoint::Profile,
+            Route::Hoyos(_) | Route::Hoyo(..) => Endpoint::Hoyos,
+            Route::Builds(..) | Route::Build(..) => Endpoint::Builds,
+        }
+    }
 }
 
 // TODO: user-provided redis client
 
 pub use self::r#async::*;
 mod r#async {
-    use super::{Error, Result, api};
-    use reqwest::{Client, header::HeaderValue};
-    use std::collections::HashMap;
+    #[cfg(feature = "stateful")]
+    use super::Player;
+    use super::{Error, Result, Route, api};
+    use crate::metrics::{Endpoint, Metrics, StatusClass};
+    use crate::{
+        cache::Cache,
+        inflight::{self, Inflight, Slot},
+        rate_limit::RateLimiter,
+    };
+    #[cfg(feature = "stateful")]
+    use futures_util::stream::{self, BoxStream, StreamExt};
+    use reqwest::{Client, Url, header::HeaderValue};
+    use std::{sync::Arc, time::Instant};
+    #[cfg(feature = "logging")]
+    use tracing::field::Empty;
 
-    async fn fetch_json<T: serde::de::DeserializeOwned>(
-        endpoint: &str,
-        user_agent: Option<HeaderValue>,
-        req_client: Option<&Client>,
-    ) -> Result<T> {
-        let request = log!({
-            use reqwest::{Method, Request, Url, header};
-            let mut r = Request::new(
-                Method::GET,
-                Url::parse("https://enka.network/")
-                    .unwrap()
-                    .join(endpoint)
-                    .unwrap(),
-            );
-            r.headers_mut().insert(
-                header::USER_AGENT,
-                user_agent.unwrap_or_else(|| {
-                    HeaderValue::from_static(concat!("enka-rs/", env!("GIT_HASH")))
-                }),
-            );
-            r
-        });
-        let response = log!(
-            req_client
+    /// Per-call state shared by the free functions and the stateful wrapper
+    ///
+    /// With the `logging` feature every endpoint call gets its own span, failures being reported
+    /// once by the span at WARN level and the position of deserialization errors at DEBUG level.
+    /// Requests are never retried, so spans carry no retry count.
+    #[derive(Clone, Copy)]
+    pub(crate) struct Context<'a> {
+        pub(crate) user_agent: Option<&'a HeaderValue>,
+        pub(crate) req_client: Option<&'a Client>,
+        pub(crate) metrics: Option<&'a dyn Metrics>,
+        pub(crate) cache: Option<&'a Cache>,
+        pub(crate) rate_limiter: Option<&'a RateLimiter>,
+        pub(crate) base_url: Option<&'a Url>,
+        pub(crate) inflight: Option<&'a Inflight<Result<Arc<str>>>>,
+    }
+
+    impl std::fmt::Debug for Context<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Context")
+                .field("user_agent", &self.user_agent)
+                .field("req_client", &self.req_client)
+                .field("metrics", &self.metrics.is_some())
+                .field("cache", &self.cache)
+                .field("rate_limiter", &self.rate_limiter)
+                .field("base_url", &self.base_url)
+                .field("inflight", &self.inflight.is_some())
+                .finish()
+        }
+    }
+
+    impl<'a> Context<'a> {
+        fn new(user_agent: Option<&'a HeaderValue>, req_client: Option<&'a Client>) -> Self {
+            Context {
+                user_agent,
+                req_client,
+                metrics: None,
+                cache: None,
+                rate_limiter: None,
+                base_url: None,
+                inflight: None,
+            }
+        }
+
+        async fn fetch_json<T: serde::de::DeserializeOwned>(
+            self,
+            family: Endpoint,
+            endpoint: &str,
+        ) -> Result<T> {
+            let Some(cache) = self.cache else {
+                return self.parse_json(family, &self.fetch_shared(family, endpoint).await?);
+            };
+            if let Some(text) = cache.get(endpoint) {
+                #[cfg(feature = "logging")]
+                tracing::Span::current().record("cache_hit", true);
+                if let Some(m) = self.metrics {
+                    m.cache_hit(family);
+                }
+                return self.parse_json(family, &text);
+            }
+            #[cfg(feature = "logging")]
+            tracing::Span::current().record("cache_hit", false);
+            if let Some(m) = self.metrics {
+                m.cache_miss(family);
+            }
+            let text = self.fetch_shared(family, endpoint).await?;
+            let value = self.parse_json(family, &text)?;
+            cache.insert(endpoint.to_owned(), text);
+            Ok(value)
+        }
+
+        async fn fetch_route<T: serde::de::DeserializeOwned>(self, route: Route<'_>) -> Result<T> {
+            self.fetch_json(route.family(), &route.path()).await
+        }
+
+        /// Fetches `endpoint`, sharing the response with identical requests already in flight
+        async fn fetch_shared(self, family: Endpoint, endpoint: &str) -> Result<Arc<str>> {
+            let Some(inflight) = self.inflight else {
+                return self.fetch_text(family, endpoint).await.map(Arc::from);
+            };
+            match inflight.join(endpoint) {
+                Slot::Leader(leader) => {
+                    let result = self.fetch_text(family, endpoint).await.map(Arc::from);
+                    leader.finish(result.clone());
+                    result
+                }
+                Slot::Follower(rx) => {
+                    #[cfg(feature = "logging")]
+                    tracing::debug!("waiting for identical request in flight");
+                    match inflight::follow(rx).await {
+                        Some(result) => result,
+                        None => self.fetch_text(family, endpoint).await.map(Arc::from),
+                    }
+                }
+            }
+        }
+
+        fn parse_json<T: serde::de::DeserializeOwned>(
+            self,
+            family: Endpoint,
+            text: &str,
+        ) -> Result<T> {
+            serde_json::from_str::<T>(text)
+                .inspect_err(|_e| {
+                    #[cfg(feature = "logging")]
+                    tracing::debug!(error = %_e, "failed to deserialize response");
+                    if let Some(m) = self.metrics {
+                        m.deserialization_failed(family);
+                    }
+                })
+                .map_err(Error::from)
+        }
+
+        async fn fetch_text(self, family: Endpoint, endpoint: &str) -> Result<String> {
+            let request = {
+                use reqwest::{Method, Request, header};
+                let mut r = Request::new(
+                    Method::GET,
+                    self.base_url
+                        .cloned()
+                        .unwrap_or_else(|| Url::parse("https://enka.network/").unwrap())
+                        .join(endpoint)
+                        .unwrap(),
+                );
+                r.headers_mut().insert(
+                    header::USER_AGENT,
+                    self.user_agent.cloned().unwrap_or_else(|| {
+                        HeaderValue::from_static(concat!("enka-rs/", env!("GIT_HASH")))
+                    }),
+                );
+                r
+            };
+            if let Some(limiter) = self.rate_limiter {
+                let wait = limiter.reserve();
+                if !wait.is_zero() {
+                    #[cfg(feature = "logging")]
+                    tracing::debug!(?wait, "waiting for rate limiter");
+                    tokio::time::sleep(wait).await;
+                    if let Some(m) = self.metrics {
+                        m.rate_limit_waited(family, wait);
+                    }
+                }
+            }
+            #[cfg(feature = "logging")]
+            tracing::debug!(url = %request.url(), "sending request");
+            if let Some(m) = self.metrics {
+                m.request_started(family);
+            }
+            let started = Instant::now();
+            let response = self
+                .req_client
                 .unwrap_or(&Client::new())
                 .execute(request)
-                .await?
-        );
-        let status = response.status();
-        if status.is_success() {
-            let text = response.text().await?;
-            serde_json::from_str::<T>(&text)
+                .await
                 .inspect_err(|_| {
-                    #[allow(unused_variables)]
-                    if let Ok(pretty_json) = serde_json::from_str::<serde_json::Value>(&text) {
-                        log!("{}", serde_json::to_string_pretty(&pretty_json).unwrap());
-                    } else {
-                        log!("Invalid JSON response: {text}");
+                    if let Some(m) = self.metrics {
+                        m.request_finished(family, StatusClass::Transport, started.elapsed());
                     }
-                })
-                .map_err(Error::Json)
-        } else {
-            let error_message = match status.as_u16() {
-                400 => "Bad Request: Wrong UID format",
-                404 => "Not Found: Player does not exist (MHY server response)",
-                424 => "Failed Dependency: Game maintenance or broken after update",
-                429 => "Too Many Requests: Rate-limited (by enka server or MHY server)",
-                500 => "Internal Server Error: General server issue",
-                503 => "Service Unavailable: Possible major failure on enka end",
-                _ => status.canonical_reason().unwrap_or("Unknown Error"),
+                })?;
+            let status = response.status();
+            #[cfg(feature = "logging")]
+            tracing::Span::current().record("status", status.as_u16());
+            let text = if status.is_success() {
+                response.text().await
+            } else {
+                Ok(response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| String::from("Failed to retrieve error body")))
             };
-            #[allow(unused_variables)]
-            let text = response
-                .text()
+            if let Some(m) = self.metrics {
+                m.request_finished(
+                    family,
+                    match text {
+                        Ok(_) => status.into(),
+                        Err(_) => StatusClass::Transport,
+                    },
+                    started.elapsed(),
+                );
+            }
+            let text = text?;
+            #[cfg(feature = "logging")]
+            {
+                let span = tracing::Span::current();
+                span.record("latency_ms", started.elapsed().as_millis() as u64);
+                span.record("bytes", text.len());
+                tracing::trace!(body = %text, "received response");
+            }
+            if status.is_success() {
+                Ok(text)
+            } else {
+                let error_message = match status.as_u16() {
+                    400 => "Bad Request: Wrong UID format",
+                    404 => "Not Found: Player does not exist (MHY server response)",
+                    424 => "Failed Dependency: Game maintenance or broken after update",
+                    429 => "Too Many Requests: Rate-limited (by enka server or MHY server)",
+                    500 => "Internal Server Error: General server issue",
+                    503 => "Service Unavailable: Possible major failure on enka end",
+                    _ => status.canonical_reason().unwrap_or("Unknown Error"),
+                };
+                Err(Error::Response(format!("HTTP {status}: {error_message}")))
+            }
+        }
+
+        #[cfg_attr(
+            feature = "logging",
+            tracing::instrument(
+                name = "get_player",
+                skip_all,
+                fields(
+                    endpoint = "uid",
+                    uid = %uid,
+                    info_only,
+                    status = Empty,
+                    latency_ms = Empty,
+                    bytes = Empty,
+                    cache_hit = Empty,
+                ),
+                err(level = "warn", Display)
+            )
+        )]
+        pub(crate) async fn get_player(
+            self,
+            uid: api::Uid,
+            info_only: bool,
+        ) -> Result<(api::player::info::Info, Option<Vec<api::AvatarInfo>>)> {
+            let url = &Route::Player { uid, info_only }.path();
+
+            if info_only {
+                self.fetch_json::<api::player::info::Response>(Endpoint::Uid, url)
+                    .await
+                    .map(|api::player::info::Response(v)| (v, None))
+            } else {
+                self.fetch_json::<api::player::Response>(Endpoint::Uid, url)
+                    .await
+                    .map(|v| (v.info, v.avatar_info_list))
+            }
+        }
+
+        #[cfg_attr(
+            feature = "logging",
+            tracing::instrument(
+                name = "get_profile",
+                skip_all,
+                fields(
+                    endpoint = "profile",
+                    username = %username,
+                    status = Empty,
+                    latency_ms = Empty,
+                    bytes = Empty,
+                    cache_hit = Empty,
+                ),
+                err(level = "warn", Display)
+            )
+        )]
+        pub(crate) async fn get_profile(
+            self,
+            username: &api::Username,
+        ) -> Result<api::profile::info::Info> {
+            self.fetch_route(Route::Profile(username)).await
+        }
+
+        #[cfg_attr(
+            feature = "logging",
+            tracing::instrument(
+                name = "get_hoyos",
+                skip_all,
+                fields(
+                    endpoint = "hoyos",
+                    username = %username,
+                    status = Empty,
+                    latency_ms = Empty,
+                    bytes = Empty,
+                    cache_hit = Empty,
+                ),
+                err(level = "warn", Display)
+            )
+        )]
+        pub(crate) async fn get_hoyos(
+            self,
+            username: &api::Username,
+        ) -> Result<api::profile::hoyos::Hoyos> {
+            self.fetch_route(Route::Hoyos(username)).await
+        }
+
+        #[cfg_attr(
+            feature = "logging",
+            tracing::instrument(
+                name = "get_hoyo",
+                skip_all,
+                fields(
+                    endpoint = "hoyo",
+                    username = %username,
+                    hash = %hash,
+                    status = Empty,
+                    latency_ms = Empty,
+                    bytes = Empty,
+                    cache_hit = Empty,
+                ),
+                err(level = "warn", Display)
+            )
+        )]
+        pub(crate) async fn get_hoyo(
+            self,
+            username: &api::Username,
+            hash: &api::HoyoHash,
+        ) -> Result<api::profile::hoyo::Hoyo> {
+            self.fetch_route(Route::Hoyo(username, hash)).await
+        }
+
+        #[cfg_attr(
+            feature = "logging",
+            tracing::instrument(
+                name = "get_builds",
+                skip_all,
+                fields(
+                    endpoint = "builds",
+                    username = %username,
+                    hash = %hash,
+                    status = Empty,
+                    latency_ms = Empty,
+                    bytes = Empty,
+                    cache_hit = Empty,
+                ),
+                err(level = "warn", Display)
+            )
+        )]
+        pub(crate) async fn get_builds(
+            self,
+            username: &api::Username,
+            hash: &api::HoyoHash,
+        ) -> Result<api::profile::hoyo::builds::Builds> {
+            self.fetch_route(Route::Builds(username, hash)).await
+        }
+
+        #[cfg_attr(
+            feature = "logging",
+            tracing::instrument(
+                name = "get_build",
+                skip_all,
+                fields(
+                    endpoint = "build",
+                    username = %username,
+                    hash = %hash,
+                    build_id,
+                    status = Empty,
+                    latency_ms = Empty,
+                    bytes = Empty,
+                    cache_hit = Empty,
+                ),
+                err(level = "warn", Display)
+            )
+        )]
+        pub(crate) async fn get_build(
+            self,
+            username: &api::Username,
+            hash: &api::HoyoHash,
+            build_id: u64,
+        ) -> Result<api::profile::hoyo::build::Build> {
+            self.fetch_route(Route::Build(username, hash, build_id))
                 .await
-                .unwrap_or_else(|_| String::from("Failed to retrieve error body"));
-            log!("Response Body: {text}");
-            Err(Error::Response(format!("HTTP {status}: {error_message}")))
         }
     }
 
     pub async fn get_player(
-        uid: u64,
+        uid: api::Uid,
         info_only: bool,
         user_agent: Option<HeaderValue>,
         req_client: Option<&Client>,
     ) -> Result<(api::player::info::Info, Option<Vec<api::AvatarInfo>>)> {
-        let url = &format!("/api/uid/{uid}/{}", if info_only { "?info" } else { "" });
-
-        if info_only {
-            fetch_json::<api::player::info::Response>(url, user_agent, req_client)
-                .await
-                .map(|api::player::info::Response(v)| (v, None))
-        } else {
-            fetch_json::<api::player::Response>(url, user_agent, req_client)
-                .await
-                .map(|v| (v.info, v.avatar_info_list))
-        }
+        Context::new(user_agent.as_ref(), req_client)
+            .get_player(uid, info_only)
+            .await
     }
 
     pub async fn get_profile(
-        username: &str,
+        username: &api::Username,
         user_agent: Option<HeaderValue>,
         req_client: Option<&Client>,
     ) -> Result<api::profile::info::Info> {
-        fetch_json(
-            &format!("api/profile/{username}/?format=json"),
-            user_agent,
-            req_client,
-        )
-        .await
+        Context::new(user_agent.as_ref(), req_client)
+            .get_profile(username)
+            .await
     }
 
     pub async fn get_hoyos(
-        username: &str,
+        username: &api::Username,
         user_agent: Option<HeaderValue>,
         req_client: Option<&Client>,
-    ) -> Result<HashMap<String, api::profile::hoyo::Hoyo>> {
-        fetch_json(
-            &format!("api/profile/{username}/hoyos"),
-            user_agent,
-            req_client,
-        )
-        .await
+    ) -> Result<api::profile::hoyos::Hoyos> {
+        Context::new(user_agent.as_ref(), req_client)
+            .get_hoyos(username)
+            .await
     }
 
     pub async fn get_hoyo(
-        username: &str,
-        hash: &api::profile::hoyo::Hash,
+        username: &api::Username,
+        hash: &api::HoyoHash,
         user_agent: Option<HeaderValue>,
         req_client: Option<&Client>,
     ) -> Result<api::profile::hoyo::Hoyo> {
-        fetch_json(
-            &format!("api/profile/{username}/hoyos/{hash}/?format=json"),
-            user_agent,
-            req_client,
-        )
-        .await
+        Context::new(user_agent.as_ref(), req_client)
+            .get_hoyo(username, hash)
+            .await
     }
 
     pub async fn get_builds(
-        username: &str,
-        hash: &api::profile::hoyo::Hash,
+        username: &api::Username,
+        hash: &api::HoyoHash,
         user_agent: Option<HeaderValue>,
         req_client: Option<&Client>,
-    ) -> Result<HashMap<api::AvatarId, Vec<api::profile::hoyo::build::Build>>> {
-        fetch_json(
-            &format!("api/profile/{username}/hoyos/{hash}/builds"),
-            user_agent,
-            req_client,
-        )
-        .await
+    ) -> Result<api::profile::hoyo::builds::Builds> {
+        Context::new(user_agent.as_ref(), req_client)
+            .get_builds(username, hash)
+            .await
     }
 
     pub async fn get_build(
-        username: &str,
-        hash: &api::profile::hoyo::Hash,
+        username: &api::Username,
+        hash: &api::HoyoHash,
         build_id: u64,
         user_agent: Option<HeaderValue>,
         req_client: Option<&Client>,
     ) -> Result<api::profile::hoyo::build::Build> {
-        fetch_json(
-            &format!("api/profile/{username}/hoyos/{hash}/builds/{build_id}"),
-            user_agent,
-            req_client,
+        Context::new(user_agent.as_ref(), req_client)
+            .get_build(username, hash, build_id)
+            .await
+    }
+
+    /// Body of the response to `route`, left unparsed
+    ///
+    /// Useful to forward responses as is or to reach data not modelled by this crate yet.
+    #[cfg_attr(
+        feature = "logging",
+        tracing::instrument(
+            name = "get_raw",
+            skip_all,
+            fields(
+                endpoint = route.family().as_str(),
+                path = %route.path(),
+                status = Empty,
+                latency_ms = Empty,
+                bytes = Empty,
+            ),
+            err(level = "warn", Display)
         )
-        .await
+    )]
+    pub async fn get_raw(
+        route: Route<'_>,
+        user_agent: Option<HeaderValue>,
+        req_client: Option<&Client>,
+    ) -> Result<String> {
+        Context::new(user_agent.as_ref(), req_client)
+            .fetch_text(route.family(), &route.path())
+            .await
     }
 
+    /// Options for [`Wrapper::get_players`]
+    #[cfg(feature = "stateful")]
+    #[derive(Debug, Clone, Copy)]
+    pub struct BulkOptions {
+        pub info_only: bool,
+        /// Maximum number of requests in flight at once, values below 1 are treated as 1
+        pub concurrency: usize,
+        /// Yield results in the same order as the UIDs were given instead of as soon as they
+        /// complete
+        pub ordered: bool,
+    }
+
+    #[cfg(feature = "stateful")]
+    impl Default for BulkOptions {
+        fn default() -> Self {
+            BulkOptions {
+                info_only: false,
+                concurrency: 4,
+                ordered: false,
+            }
+        }
+    }
+
+    /// Everything enka.network knows about a player, as returned by [`Wrapper::get_player_full`]
     #[cfg(feature = "stateful")]
     #[derive(Debug)]
-    pub struct Wrapper<'a> {
-        pub(crate) user_agent: &'a Option<HeaderValue>,
-        pub(crate) req_client: &'a Option<Client>,
+    pub struct FullPlayer {
+        /// Player info, the owning profile is available through [`api::player::info::Info::owner`]
+        pub info: api::player::info::Info,
+        pub avatar_info_list: Option<Vec<api::AvatarInfo>>,
+        /// Hoyo the UID is linked to, `None` if the UID is not linked to any profile or the lookup
+        /// failed
+        pub hoyo: Option<api::profile::hoyo::Hoyo>,
+        /// Saved builds, `None` if the UID is not linked to any profile, its hoyo is private or the
+        /// lookup failed
+        pub builds: Option<api::profile::hoyo::builds::Builds>,
+        /// Failure of the hoyo or builds lookup, e.g. after the UID was unlinked from its profile
+        pub profile_error: Option<Error>,
     }
 
     #[cfg(feature = "stateful")]
-    impl Wrapper<'_> {
+    impl FullPlayer {
+        pub fn owner(&self) -> Option<&api::player::info::Owner> {
+            self.info.owner.as_ref()
+        }
+    }
+
+    /// Genshin Impact handle of an [`crate::EnkaClient`], cheap to clone and to move across tasks
+    #[cfg(feature = "stateful")]
+    #[derive(Debug, Clone)]
+    pub struct Wrapper {
+        pub(crate) client: crate::EnkaClient,
+    }
+
+    #[cfg(feature = "stateful")]
+    impl Wrapper {
         pub async fn get_player(
             &self,
-            uid: u64,
+            uid: api::Uid,
             info_only: bool,
         ) -> Result<(api::player::info::Info, Option<Vec<api::AvatarInfo>>)> {
-            get_player(
-                uid,
-                info_only,
-                self.user_agent.clone(),
-                self.req_client.as_ref(),
-            )
-            .await
+            self.client.context().get_player(uid, info_only).await
+        }
+
+        /// Fetches a player along with the hoyo and saved builds of the profile owning it
+        ///
+        /// Only fails when the player itself cannot be fetched, failures of the later lookups are
+        /// reported in [`FullPlayer::profile_error`].
+        pub async fn get_player_full(&self, uid: api::Uid) -> Result<FullPlayer> {
+            let (info, avatar_info_list) = self.get_player(uid, false).await?;
+            let mut player = FullPlayer {
+                info,
+                avatar_info_list,
+                hoyo: None,
+                builds: None,
+                profile_error: None,
+            };
+            let Some(owner) = &player.info.owner else {
+                return Ok(player);
+            };
+            let hoyo = match self.get_hoyo(&owner.info.username, &owner.hash).await {
+                Ok(v) => v,
+                Err(e) => {
+                    player.profile_error = Some(e);
+                    return Ok(player);
+                }
+            };
+            if hoyo.is_public() {
+                match self.get_builds(&owner.info.username, &owner.hash).await {
+                    Ok(v) => player.builds = Some(v),
+                    Err(e) => player.profile_error = Some(e),
+                }
+            }
+            player.hoyo = Some(hoyo);
+            Ok(player)
         }
 
-        pub async fn get_profile(&self, username: &str) -> Result<api::profile::info::Info> {
-            get_profile(username, self.user_agent.clone(), self.req_client.as_ref()).await
+        /// Fetches every UID in `uids` through [`Wrapper::get_player`], skipping repeated UIDs
+        ///
+        /// Requests go through the rate limiter and cache of the underlying [`crate::EnkaClient`].
+        pub fn get_players(
+            &self,
+            uids: impl IntoIterator<Item = api::Uid>,
+            options: BulkOptions,
+        ) -> BoxStream<'static, (api::Uid, Result<Player>)> {
+            let mut seen = std::collections::HashSet::new();
+            let uids = uids
+                .into_iter()
+                .filter(|uid| seen.insert(*uid))
+                .collect::<Vec<_>>();
+            let wrapper = self.clone();
+            let requests = stream::iter(uids).map(move |uid| {
+                let wrapper = wrapper.clone();
+                async move { (uid, wrapper.get_player(uid, options.info_only).await) }
+            });
+            let concurrency = options.concurrency.max(1);
+            if options.ordered {
+                requests.buffered(concurrency).boxed()
+            } else {
+                requests.buffer_unordered(concurrency).boxed()
+            }
+        }
+
+        pub async fn get_profile(
+            &self,
+            username: &api::Username,
+        ) -> Result<api::profile::info::Info> {
+            self.client.context().get_profile(username).await
         }
 
         pub async fn get_hoyos(
             &self,
-            username: &str,
-        ) -> Result<HashMap<String, api::profile::hoyo::Hoyo>> {
-            get_hoyos(username, self.user_agent.clone(), self.req_client.as_ref()).await
+            username: &api::Username,
+        ) -> Result<api::profile::hoyos::Hoyos> {
+            self.client.context().get_hoyos(username).await
         }
 
         pub async fn get_hoyo(
             &self,
-            username: &str,
-            hash: &api::profile::hoyo::Hash,
+            username: &api::Username,
+            hash: &api::HoyoHash,
         ) -> Result<api::profile::hoyo::Hoyo> {
-            get_hoyo(
-                username,
-                hash,
-                self.user_agent.clone(),
-                self.req_client.as_ref(),
-            )
-            .await
+            self.client.context().get_hoyo(username, hash).await
         }
 
         pub async fn get_builds(
             &self,
-            username: &str,
-            hash: &api::profile::hoyo::Hash,
-        ) -> Result<HashMap<api::AvatarId, Vec<api::profile::hoyo::build::Build>>> {
-            get_builds(
-                username,
-                hash,
-                self.user_agent.clone(),
-                self.req_client.as_ref(),
-            )
-            .await
+            username: &api::Username,
+            hash: &api::HoyoHash,
+        ) -> Result<api::profile::hoyo::builds::Builds> {
+            self.client.context().get_builds(username, hash).await
         }
 
         pub async fn get_build(
             &self,
-            username: &str,
-            hash: &api::profile::hoyo::Hash,
+            username: &api::Username,
+            hash: &api::HoyoHash,
             build_id: u64,
         ) -> Result<api::profile::hoyo::build::Build> {
-            get_build(
-                username,
-                hash,
-                build_id,
-                self.user_agent.clone(),
-                self.req_client.as_ref(),
-            )
-            .await
+            self.client
+                .context()
+                .get_build(username, hash, build_id)
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Route,
+        api::{HoyoHash, Uid, Username},
+    };
+    use crate::metrics::Endpoint;
+
+    #[test]
+    fn routes() {
+        let uid = Uid::new(618285856).unwrap();
+        let username = Username::new("Алиса").unwrap();
+        let hash = HoyoHash::new("4Wjv2e").unwrap();
+
+        let player = Route::Player {
+            uid,
+            info_only: true,
+        };
+        assert_eq!(player.path(), "api/uid/618285856/?info");
+        assert_eq!(player.family(), Endpoint::Uid);
+
+        let build = Route::Build(&username, &hash, 42);
+        assert_eq!(
+            build.path(),
+            "api/profile/%D0%90%D0%BB%D0%B8%D1%81%D0%B0/hoyos/4Wjv2e/builds/42"
+        );
+        assert_eq!(build.family(), Endpoint::Builds);
+        assert_eq!(Route::Hoyos(&username).family(), Endpoint::Hoyos);
+    }
+
+    #[cfg(feature = "stateful")]
+    mod coalescing {
+        use crate::{Config, EnkaClient, gi::Error};
+        use reqwest::Url;
+        use std::{
+            sync::{
+                Arc,
+                atomic::{AtomicUsize, Ordering},
+            },
+            time::Duration,
+        };
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+            sync::mpsc,
+        };
+
+        /// Local server answering every request with `status` and the player fixture after
+        /// `delay`, reporting each request it receives on the returned channel
+        async fn mock(
+            status: u16,
+            delay: Duration,
+        ) -> (EnkaClient, Arc<AtomicUsize>, mpsc::UnboundedReceiver<()>) {
+            let body = std::fs::read_to_string(format!(
+                "{}/tests/fixtures/player.json",
+                env!("CARGO_MANIFEST_DIR")
+            ))
+            .unwrap();
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+            let requests = Arc::new(AtomicUsize::new(0));
+            let (tx, rx) = mpsc::unbounded_channel();
+            let counter = requests.clone();
+            tokio::spawn(async move {
+                loop {
+                    let (mut socket, _) = listener.accept().await.unwrap();
+                    let (body, counter, tx) = (body.clone(), counter.clone(), tx.clone());
+                    tokio::spawn(async move {
+                        let mut request = Vec::new();
+                        let mut buf = [0; 1024];
+                        while !request.ends_with(b"\r\n\r\n") {
+                            let n = socket.read(&mut buf).await.unwrap();
+                            if n == 0 {
+                                return;
+                            }
+                            request.extend_from_slice(&buf[..n]);
+                        }
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        let _ = tx.send(());
+                        tokio::time::sleep(delay).await;
+                        let response = format!(
+                            "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                            body.len()
+                        );
+                        let _ = socket.write_all(response.as_bytes()).await;
+                    });
+                }
+            });
+            let client = EnkaClient::new(Config {
+                base_url: Some(url),
+                ..Default::default()
+            });
+            (client, requests, rx)
+        }
+
+        fn uid() -> crate::gi::api::Uid {
+            crate::gi::api::Uid::new(618285856).unwrap()
+        }
+
+        #[tokio::test]
+        async fn shared_response() {
+            let (client, requests, _rx) = mock(200, Duration::from_millis(100)).await;
+            let gi = client.gi();
+            let results =
+                futures_util::future::join_all((0..5).map(|_| gi.get_player(uid(), false))).await;
+            assert_eq!(requests.load(Ordering::SeqCst), 1);
+            for result in results {
+                let (info, avatars) = result.unwrap();
+                assert_eq!(info.uid, uid());
+                assert_eq!(avatars.map(|v| v.len()), Some(1));
+            }
+            // a later call is not coalesced with a finished one
+            gi.get_player(uid(), false).await.unwrap();
+            assert_eq!(requests.load(Ordering::SeqCst), 2);
+        }
+
+        #[tokio::test]
+        async fn shared_error() {
+            let (client, requests, _rx) = mock(500, Duration::from_millis(100)).await;
+            let gi = client.gi();
+            let results =
+                futures_util::future::join_all((0..3).map(|_| gi.get_player(uid(), false))).await;
+            assert_eq!(requests.load(Ordering::SeqCst), 1);
+            for result in results {
+                assert!(matches!(result, Err(Error::Response(e)) if e.starts_with("HTTP 500")));
+            }
+        }
+
+        #[tokio::test]
+        async fn cancelled_leader() {
+            let (client, requests, mut rx) = mock(200, Duration::from_millis(200)).await;
+            let gi = client.gi();
+            let leader = tokio::spawn({
+                let gi = gi.clone();
+                async move { gi.get_player(uid(), false).await }
+            });
+            rx.recv().await.unwrap();
+            let follower = tokio::spawn({
+                let gi = gi.clone();
+                async move { gi.get_player(uid(), false).await }
+            });
+            // let the follower join the leader's request before cancelling it
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            leader.abort();
+            assert!(leader.await.unwrap_err().is_cancelled());
+            assert_eq!(follower.await.unwrap().unwrap().0.uid, uid());
+            assert_eq!(requests.load(Ordering::SeqCst), 2);
         }
     }
 }
@@ -1,5 +1,5 @@
-use serde::Deserialize;
-use serde_repr::Deserialize_repr;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::collections::HashMap;
 
 pub mod player {
@@ -21,18 +21,18 @@ pub mod player {
         #[derive(Deserialize, Debug)]
         pub struct Response(pub Info);
 
-        #[derive(Deserialize, Debug)]
+        #[derive(Deserialize, Serialize, Debug, Clone)]
         #[serde(rename_all = "camelCase", deny_unknown_fields)]
         pub struct Info {
             pub player_info: PlayerInfo,
             pub ttl: u64,
-            pub uid: String, // WHY
+            pub uid: Uid,
             pub owner: Option<Owner>,
         }
 
-        #[derive(Deserialize, Debug)]
+        #[derive(Deserialize, Serialize, Debug, Clone)]
         pub struct Owner {
-            pub hash: profile::hoyo::Hash,
+            pub hash: HoyoHash,
             #[serde(flatten)]
             pub info: profile::info::Info,
         }
@@ -49,15 +49,15 @@ pub mod profile {
         #[derive(Deserialize, Debug)]
         pub struct Response(pub Info);
 
-        #[derive(Deserialize, Debug)]
+        #[derive(Deserialize, Serialize, Debug, Clone)]
         #[serde(deny_unknown_fields)]
         pub struct Info {
-            pub username: String,
+            pub username: Username,
             pub profile: Profile,
             pub id: u64,
         }
 
-        #[derive(Deserialize, Debug)]
+        #[derive(Deserialize, Serialize, Debug, Clone)]
         #[serde(deny_unknown_fields)]
         pub struct Profile {
             pub bio: String,
@@ -73,12 +73,91 @@ pub mod profile {
 
         /// url: https://enka.network/api/profile/{owner.username}/hoyos/
         #[derive(Deserialize, Debug)]
-        pub struct Response(pub HashMap<hoyo::Hash, hoyo::Hoyo>);
+        pub struct Response(pub Hoyos);
+
+        /// Game accounts linked to a profile, keyed by their hash
+        #[derive(Deserialize, Debug, Default)]
+        #[serde(transparent)]
+        pub struct Hoyos(pub HashMap<HoyoHash, hoyo::Hoyo>);
+
+        impl Hoyos {
+            /// Genshin Impact accounts
+            pub fn genshin(&self) -> impl Iterator<Item = &hoyo::GenshinHoyo> {
+                self.0.values().filter_map(|v| match v {
+                    hoyo::Hoyo::Genshin(v) => Some(v),
+                    hoyo::Hoyo::Other(_) => None,
+                })
+            }
+
+            /// Honkai: Star Rail accounts, left untyped for now
+            pub fn star_rail(&self) -> impl Iterator<Item = &serde_json::Value> {
+                self.others(1)
+            }
+
+            /// Zenless Zone Zero accounts, left untyped for now
+            pub fn zenless(&self) -> impl Iterator<Item = &serde_json::Value> {
+                self.others(2)
+            }
+
+            fn others(&self, hoyo_type: u8) -> impl Iterator<Item = &serde_json::Value> {
+                self.0.values().filter_map(move |v| match v {
+                    hoyo::Hoyo::Other(v) if v["hoyo_type"].as_u64() == Some(hoyo_type.into()) => {
+                        Some(v)
+                    }
+                    _ => None,
+                })
+            }
+
+            /// Accounts whose ownership was verified by enka.network
+            pub fn verified(&self) -> impl Iterator<Item = &hoyo::Hoyo> {
+                self.0.values().filter(|v| v.is_verified())
+            }
+
+            /// Accounts visible to everyone
+            pub fn public(&self) -> impl Iterator<Item = &hoyo::Hoyo> {
+                self.0.values().filter(|v| v.is_public())
+            }
+
+            /// Account with the given in-game UID, only matches accounts whose UID is public
+            pub fn by_uid(&self, uid: Uid) -> Option<&hoyo::Hoyo> {
+                self.0.values().find(|v| v.uid() == Some(uid))
+            }
+
+            pub fn by_hash(&self, hash: &str) -> Option<&hoyo::Hoyo> {
+                self.0.get(hash)
+            }
+
+            /// Hoyos in the order the user arranged them on enka.network
+            pub fn iter_ordered(&self) -> impl Iterator<Item = (&HoyoHash, &hoyo::Hoyo)> {
+                let mut hoyos = self.0.iter().collect::<Vec<_>>();
+                hoyos.sort_by_cached_key(|(hash, hoyo)| {
+                    let order = hoyo.order();
+                    (order.is_none(), order, *hash)
+                });
+                hoyos.into_iter()
+            }
+        }
+
+        impl std::ops::Deref for Hoyos {
+            type Target = HashMap<HoyoHash, hoyo::Hoyo>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl IntoIterator for Hoyos {
+            type Item = (HoyoHash, hoyo::Hoyo);
+            type IntoIter = std::collections::hash_map::IntoIter<HoyoHash, hoyo::Hoyo>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.0.into_iter()
+            }
+        }
     }
 
     pub mod hoyo {
         use super::*;
-        pub type Hash = String;
 
         /// url: https://enka.network/api/profile/{owner.username}/hoyos/{owner.hash}/?format=json
         #[derive(Deserialize, Debug)]
@@ -95,6 +174,38 @@ pub mod profile {
             Other(serde_json::Value),
         }
 
+        impl Hoyo {
+            /// In-game UID of the account, if public and valid
+            pub fn uid(&self) -> Option<Uid> {
+                match self {
+                    Hoyo::Genshin(v) => v.uid,
+                    Hoyo::Other(v) => Uid::new(v["uid"].as_u64()?).ok(),
+                }
+            }
+
+            pub fn is_verified(&self) -> bool {
+                match self {
+                    Hoyo::Genshin(v) => v.verified,
+                    Hoyo::Other(v) => v["verified"].as_bool().unwrap_or(false),
+                }
+            }
+
+            pub fn is_public(&self) -> bool {
+                match self {
+                    Hoyo::Genshin(v) => v.public,
+                    Hoyo::Other(v) => v["public"].as_bool().unwrap_or(false),
+                }
+            }
+
+            /// Position of the hoyo among the ones of its profile
+            pub fn order(&self) -> Option<Order> {
+                match self {
+                    Hoyo::Genshin(v) => Some(v.order.clone()),
+                    Hoyo::Other(v) => Order::deserialize(&v["order"]).ok(),
+                }
+            }
+        }
+
         // HACK: necessary due to https://github.com/serde-rs/serde/issues/745
         impl<'de> Deserialize<'de> for Hoyo {
             fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
@@ -111,22 +222,37 @@ pub mod profile {
         #[derive(Deserialize, Debug)]
         #[serde(deny_unknown_fields)]
         pub struct GenshinHoyo {
-            pub uid: Option<u64>,
+            pub uid: Option<Uid>,
             pub uid_public: bool,
             pub public: bool,
             pub live_public: bool,
             pub verified: bool,
             pub player_info: PlayerInfo,
-            pub hash: Hash,
+            pub hash: HoyoHash,
             pub region: Region,
-            pub order: String,
+            pub order: Order,
             pub avatar_order: Option<HashMap<AvatarId, u64>>,
             pub hoyo_type: u8, // TODO: check, HoyoKind I assume 0 gi, 1 hsr, 2 zzz
             pub live_data_hash: u64,
             pub user: info::Info,
         }
 
-        #[derive(Deserialize, Debug)]
+        impl GenshinHoyo {
+            /// Characters in the order the user arranged them on enka.network, empty if no
+            /// ordering was saved
+            pub fn ordered_avatars(&self) -> Vec<AvatarId> {
+                let mut avatars = self
+                    .avatar_order
+                    .iter()
+                    .flatten()
+                    .map(|(id, position)| (*position, *id))
+                    .collect::<Vec<_>>();
+                avatars.sort_unstable();
+                avatars.into_iter().map(|(_, id)| id).collect()
+            }
+        }
+
+        #[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
         #[serde(rename_all = "UPPERCASE")]
         pub enum Region {
             #[serde(rename = "")]
@@ -149,7 +275,90 @@ pub mod profile {
 
             /// url: https://enka.network/api/profile/{owner.username}/hoyos/{owner.hash}/builds/
             #[derive(Deserialize, Debug)]
-            pub struct Response(pub HashMap<AvatarId, Vec<build::Build>>);
+            pub struct Response(pub Builds);
+
+            /// Builds of a hoyo, grouped by character
+            #[derive(Deserialize, Debug, Default)]
+            #[serde(transparent)]
+            pub struct Builds(pub HashMap<AvatarId, Vec<build::Build>>);
+
+            impl Builds {
+                /// Most recent live snapshot of a character
+                pub fn live(&self, avatar_id: AvatarId) -> Option<&build::Build> {
+                    self.0
+                        .get(&avatar_id)?
+                        .iter()
+                        .filter(|v| v.live)
+                        .max_by_key(|v| v.id)
+                }
+
+                /// Builds of a character saved by the user, in the order they arranged them
+                pub fn saved(&self, avatar_id: AvatarId) -> impl Iterator<Item = &build::Build> {
+                    self.iter_ordered_for(avatar_id).filter(|v| !v.live)
+                }
+
+                /// Drops every build not visible to everyone
+                pub fn public_only(mut self) -> Self {
+                    self.0.retain(|_, builds| {
+                        builds.retain(|v| v.public);
+                        !builds.is_empty()
+                    });
+                    self
+                }
+
+                /// Builds the user gave the given name, across every character
+                pub fn named<'a>(
+                    &'a self,
+                    name: &'a str,
+                ) -> impl Iterator<Item = &'a build::Build> {
+                    self.0.values().flatten().filter(move |v| v.name == name)
+                }
+
+                /// Builds grouped by character, characters sorted by id and builds in the order
+                /// the user arranged them
+                pub fn iter_by_avatar(
+                    &self,
+                ) -> impl Iterator<Item = (AvatarId, impl Iterator<Item = &build::Build>)>
+                {
+                    let mut avatars = self.0.keys().copied().collect::<Vec<_>>();
+                    avatars.sort_unstable();
+                    avatars
+                        .into_iter()
+                        .map(|id| (id, self.iter_ordered_for(id)))
+                }
+
+                /// Builds of a character in the order the user arranged them on enka.network
+                pub fn iter_ordered_for(
+                    &self,
+                    avatar_id: AvatarId,
+                ) -> impl Iterator<Item = &build::Build> {
+                    let mut builds = self
+                        .0
+                        .get(&avatar_id)
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>();
+                    builds.sort_by(|a, b| (&a.order, a.id).cmp(&(&b.order, b.id)));
+                    builds.into_iter()
+                }
+            }
+
+            impl std::ops::Deref for Builds {
+                type Target = HashMap<AvatarId, Vec<build::Build>>;
+
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+
+            impl IntoIterator for Builds {
+                type Item = (AvatarId, Vec<build::Build>);
+                type IntoIter = std::collections::hash_map::IntoIter<AvatarId, Vec<build::Build>>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    self.0.into_iter()
+                }
+            }
         }
 
         pub mod build {
@@ -164,32 +373,460 @@ pub mod profile {
             pub struct Build {
                 pub id: u64,
                 pub name: String,
-                pub avatar_id: String, // this is an AvatarId as a String
+                pub avatar_id: AvatarId,
                 pub avatar_data: AvatarInfo,
-                pub order: String,
+                pub order: Order,
                 pub live: bool,
                 pub settings: Settings,
                 pub public: bool,
                 pub image: Option<String>,
                 pub hoyo_type: u8,
-                pub hoyo: Hash,
-                pub owner: String,
+                pub hoyo: HoyoHash,
+                pub owner: Username,
             }
 
-            #[derive(Deserialize, Debug)]
+            /// Layout of the build card as customized by the user
+            #[derive(Deserialize, Debug, Clone)]
             #[serde(rename_all = "camelCase", deny_unknown_fields)]
             pub struct Settings {
                 pub adaptive_color: Option<bool>,
-                pub art_source: Option<String>,
+                pub art_source: Option<ArtSource>,
                 pub caption: Option<String>,
                 pub honkard_width: Option<f64>,
-                pub transform: Option<serde_json::Value>,
+                pub transform: Option<Transform>,
+            }
+
+            /// Art displayed on the build card
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            pub enum ArtSource {
+                /// Default art chosen by enka.network
+                Default,
+                /// Character splash art
+                Splash,
+                /// Character wish (gacha) art
+                Gacha,
+                /// Image uploaded by the user, see [`Build::image`]
+                Custom,
+                /// Value not known to this crate
+                Other(String),
+            }
+
+            impl<'de> Deserialize<'de> for ArtSource {
+                fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                    Ok(match String::deserialize(d)?.as_str() {
+                        "" | "default" => ArtSource::Default,
+                        "splash" => ArtSource::Splash,
+                        "gacha" => ArtSource::Gacha,
+                        "custom" => ArtSource::Custom,
+                        other => ArtSource::Other(other.to_owned()),
+                    })
+                }
+            }
+
+            /// Placement of the art on the build card
+            ///
+            /// Missing values default to the identity transform, unknown keys are kept in
+            /// [`Transform::extra`] since the card editor may add new ones at any time.
+            #[derive(Deserialize, Debug, Clone, PartialEq)]
+            #[serde(default)]
+            pub struct Transform {
+                /// Horizontal offset, as stored by the card editor
+                pub x: f64,
+                /// Vertical offset, as stored by the card editor
+                pub y: f64,
+                pub scale: f64,
+                /// Clockwise rotation, in degrees
+                pub rotation: f64,
+                /// Mirrored horizontally
+                pub flip: bool,
+                #[serde(flatten)]
+                pub extra: HashMap<String, serde_json::Value>,
+            }
+
+            impl Default for Transform {
+                fn default() -> Self {
+                    Transform {
+                        x: 0.0,
+                        y: 0.0,
+                        scale: 1.0,
+                        rotation: 0.0,
+                        flip: false,
+                        extra: HashMap::new(),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Position of an item among its siblings, as arranged by the user on enka.network
+///
+/// Positions are either plain indices or lexicographically sorted keys, indices sort first.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Order {
+    Index(u64),
+    Key(String),
+}
+
+impl<'de> Deserialize<'de> for Order {
+    fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            String(String),
+            U64(u64),
+        }
+        Ok(match Repr::deserialize(d)? {
+            Repr::String(s) => s.parse().map_or(Order::Key(s), Order::Index),
+            Repr::U64(v) => Order::Index(v),
+        })
+    }
+}
+
+/// Genshin Impact player UID
+///
+/// UIDs are 9 digits long, the first one identifying the server region and being `0` for
+/// internal accounts. 10 digit UIDs carry a leading `1` before the region digit (e.g. `18` for
+/// Asia). UIDs built by the user are validated, UIDs sent by enka.network are trusted as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Uid(u64);
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum UidError {
+    #[error("UID is not a number")]
+    NotANumber,
+    #[error("UID must be 9 or 10 digits long")]
+    Length,
+    #[error("UID does not start with a known region prefix")]
+    Region,
+}
+
+impl Uid {
+    pub fn new(uid: u64) -> Result<Self, UidError> {
+        // 9 digits with leading zeros, or 10 digits starting with `1`
+        if !(1..2_000_000_000).contains(&uid) {
+            return Err(UidError::Length);
+        }
+        let uid = Uid(uid);
+        uid.try_region().map(|_| uid)
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+
+    /// Server region the UID belongs to, inferred from its prefix, `None` for an unknown prefix
+    /// sent by enka.network
+    pub fn region(&self) -> Option<profile::hoyo::Region> {
+        self.try_region().ok()
+    }
+
+    fn try_region(&self) -> Result<profile::hoyo::Region, UidError> {
+        use profile::hoyo::Region;
+        match self.0 / 100_000_000 % 10 {
+            0 => Ok(Region::Internal),
+            1..=3 => Ok(Region::Celestia),
+            5 => Ok(Region::Irminsul),
+            6 => Ok(Region::America),
+            7 => Ok(Region::Europe),
+            8 => Ok(Region::Asia),
+            9 => Ok(Region::TaiwanHonKongMacao),
+            _ => Err(UidError::Region),
+        }
+    }
+}
+
+impl std::fmt::Display for Uid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:09}", self.0)
+    }
+}
+
+impl std::str::FromStr for Uid {
+    type Err = UidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(UidError::NotANumber);
+        }
+        if !(9..=10).contains(&s.len()) || (s.len() == 10 && !s.starts_with('1')) {
+            return Err(UidError::Length);
+        }
+        Uid::new(s.parse().map_err(|_| UidError::NotANumber)?)
+    }
+}
+
+impl TryFrom<u64> for Uid {
+    type Error = UidError;
+
+    fn try_from(uid: u64) -> Result<Self, Self::Error> {
+        Uid::new(uid)
+    }
+}
+
+impl From<Uid> for u64 {
+    fn from(Uid(uid): Uid) -> Self {
+        uid
+    }
+}
+
+impl Serialize for Uid {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+// `Info::uid` is sent as a string while `GenshinHoyo::uid` is sent as a number
+impl<'de> Deserialize<'de> for Uid {
+    fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            String(String),
+            U64(u64),
+        }
+        Ok(Uid(match Repr::deserialize(d)? {
+            Repr::String(s) => s.parse().map_err(serde::de::Error::custom)?,
+            Repr::U64(v) => v,
+        }))
+    }
+}
+
+/// Characters left as is when a [`Username`] or [`HoyoHash`] is used as a URL path segment
+const PATH_SEGMENT: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// enka.network account username
+///
+/// Usernames built by the user are validated to only contain alphanumeric characters, `_` and
+/// `-`, usernames sent by enka.network are trusted as is. Either way they are percent-encoded when
+/// used to build a request URL.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Username(String);
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum UsernameError {
+    #[error("Username is empty")]
+    Empty,
+    #[error("Username contains the invalid character {0:?}")]
+    InvalidCharacter(char),
+}
+
+impl Username {
+    pub fn new(username: impl Into<String>) -> Result<Self, UsernameError> {
+        let username = username.into();
+        if username.is_empty() {
+            return Err(UsernameError::Empty);
+        }
+        match username
+            .chars()
+            .find(|c| !(c.is_alphanumeric() || matches!(c, '_' | '-')))
+        {
+            Some(c) => Err(UsernameError::InvalidCharacter(c)),
+            None => Ok(Username(username)),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub(crate) fn path_segment(&self) -> percent_encoding::PercentEncode<'_> {
+        percent_encoding::utf8_percent_encode(&self.0, PATH_SEGMENT)
+    }
+}
+
+/// Identifier of a game account linked to an enka.network profile
+///
+/// Hashes built by the user are validated to only contain ASCII alphanumeric characters, hashes
+/// sent by enka.network are trusted as is. Either way they are percent-encoded when used to build a
+/// request URL.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct HoyoHash(String);
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum HoyoHashError {
+    #[error("Hoyo hash is empty")]
+    Empty,
+    #[error("Hoyo hash contains the invalid character {0:?}")]
+    InvalidCharacter(char),
+}
+
+impl HoyoHash {
+    pub fn new(hash: impl Into<String>) -> Result<Self, HoyoHashError> {
+        let hash = hash.into();
+        if hash.is_empty() {
+            return Err(HoyoHashError::Empty);
+        }
+        match hash.chars().find(|c| !c.is_ascii_alphanumeric()) {
+            Some(c) => Err(HoyoHashError::InvalidCharacter(c)),
+            None => Ok(HoyoHash(hash)),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub(crate) fn path_segment(&self) -> percent_encoding::PercentEncode<'_> {
+        percent_encoding::utf8_percent_encode(&self.0, PATH_SEGMENT)
+    }
+}
+
+macro_rules! impl_string_newtype {
+    ($name:ident, $error:ident) => {
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::new(s)
             }
         }
+
+        impl TryFrom<String> for $name {
+            type Error = $error;
+
+            fn try_from(s: String) -> Result<Self, Self::Error> {
+                $name::new(s)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = $error;
+
+            fn try_from(s: &str) -> Result<Self, Self::Error> {
+                $name::new(s)
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl std::borrow::Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl From<$name> for String {
+            fn from($name(s): $name) -> Self {
+                s
+            }
+        }
+    };
+}
+
+impl_string_newtype!(Username, UsernameError);
+impl_string_newtype!(HoyoHash, HoyoHashError);
+
+/// Character identifier
+///
+/// Sent either as a number or as a string depending on the endpoint.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AvatarId(pub u64);
+
+impl AvatarId {
+    pub const AETHER: AvatarId = AvatarId(10000005);
+    pub const LUMINE: AvatarId = AvatarId(10000007);
+
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+
+    pub fn is_traveler(&self) -> bool {
+        matches!(*self, AvatarId::AETHER | AvatarId::LUMINE)
+    }
+
+    /// Element of the traveler when using the given skill depot, `None` for other characters and
+    /// for the elementless depot the traveler starts with
+    pub fn traveler_element(&self, skill_depot_id: SkillId) -> Option<Element> {
+        if !self.is_traveler() || skill_depot_id / 100 != self.0 % 100 {
+            return None;
+        }
+        match skill_depot_id % 100 {
+            2 => Some(Element::Pyro),
+            3 => Some(Element::Hydro),
+            4 => Some(Element::Anemo),
+            6 => Some(Element::Geo),
+            7 => Some(Element::Electro),
+            8 => Some(Element::Dendro),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AvatarId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::str::FromStr for AvatarId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(AvatarId)
+    }
+}
+
+impl From<u64> for AvatarId {
+    fn from(id: u64) -> Self {
+        AvatarId(id)
+    }
+}
+
+impl From<AvatarId> for u64 {
+    fn from(AvatarId(id): AvatarId) -> Self {
+        id
+    }
+}
+
+impl<'de> Deserialize<'de> for AvatarId {
+    fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            String(String),
+            U64(u64),
+        }
+        match Repr::deserialize(d)? {
+            Repr::String(s) => s.parse().map_err(serde::de::Error::custom),
+            Repr::U64(v) => Ok(AvatarId(v)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Element {
+    Pyro,
+    Hydro,
+    Anemo,
+    Electro,
+    Dendro,
+    Cryo,
+    Geo,
+}
+
+impl std::fmt::Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
     }
 }
 
-pub type AvatarId = u64;
 pub type CostumeId = u64;
 pub type ItemId = u64;
 pub type NameCardId = u64;
@@ -197,14 +834,23 @@ pub type ProfilePictureId = u64;
 pub type SkillId = u64; // TODO: check, may be TalentId
 pub type TalentId = u64;
 
-#[derive(Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Hash, Clone)]
 #[serde(untagged)]
 pub enum TextMapHash {
     String(String),
     U64(u64), // old builds may still use u64 in db
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl std::fmt::Display for TextMapHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextMapHash::String(v) => f.write_str(v),
+            TextMapHash::U64(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct PlayerInfo {
     pub nickname: String,
@@ -229,7 +875,205 @@ pub struct PlayerInfo {
     pub fetter_count: Option<u8>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// Views grouping the endgame progress fields of [`PlayerInfo`]
+///
+/// The game leaves these fields out when their value would be 0, which happens both when the
+/// player has not attempted the mode during the current period and when the mode is hidden from
+/// their showcase: the two cases cannot be told apart, and both yield `None`.
+impl PlayerInfo {
+    /// Deepest Spiral Abyss chamber cleared during the current period
+    pub fn spiral_abyss(&self) -> Option<SpiralAbyss> {
+        Some(SpiralAbyss {
+            floor: self.tower_floor_index?,
+            chamber: self.tower_level_index?,
+            stars: self.tower_star_index,
+        })
+    }
+
+    /// Hardest Stygian Onslaught difficulty cleared during the current season
+    pub fn stygian_onslaught(&self) -> Option<StygianOnslaught> {
+        Some(StygianOnslaught {
+            season: self.stygian_id,
+            difficulty: self.stygian_index?.into(),
+            clear_time: self.stygian_seconds.map(std::time::Duration::from_secs),
+        })
+    }
+
+    /// Furthest Imaginarium Theater act reached during the current season
+    pub fn imaginarium_theater(&self) -> Option<ImaginariumTheater> {
+        Some(ImaginariumTheater {
+            difficulty: self.theater_mode_index?.into(),
+            act: self.theater_act_index?,
+            stars: self.theater_star_index,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpiralAbyss {
+    /// 1 to 12
+    pub floor: u8,
+    /// 1 to 3
+    pub chamber: u8,
+    /// Stars collected over the whole period, out of 36, `None` when none were
+    pub stars: Option<u8>,
+}
+
+impl std::fmt::Display for SpiralAbyss {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.floor, self.chamber)?;
+        if let Some(stars) = self.stars {
+            write!(f, " ({stars}★)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StygianOnslaught {
+    /// Season the progress belongs to, absent on profiles fetched before it was reported
+    pub season: Option<u64>,
+    pub difficulty: StygianDifficulty,
+    /// Best clear time at [`Self::difficulty`]
+    pub clear_time: Option<std::time::Duration>,
+}
+
+/// Difficulty, named as in the English version of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StygianDifficulty {
+    Normal,
+    Hard,
+    Fearless,
+    Dire,
+    Bane,
+    Lethal,
+    /// Index not known to this version of the crate
+    Other(u8),
+}
+
+impl StygianDifficulty {
+    /// Index as reported in [`PlayerInfo::stygian_index`], starting at 1
+    pub fn index(&self) -> u8 {
+        match self {
+            StygianDifficulty::Normal => 1,
+            StygianDifficulty::Hard => 2,
+            StygianDifficulty::Fearless => 3,
+            StygianDifficulty::Dire => 4,
+            StygianDifficulty::Bane => 5,
+            StygianDifficulty::Lethal => 6,
+            StygianDifficulty::Other(index) => *index,
+        }
+    }
+}
+
+impl From<u8> for StygianDifficulty {
+    fn from(index: u8) -> Self {
+        match index {
+            1 => StygianDifficulty::Normal,
+            2 => StygianDifficulty::Hard,
+            3 => StygianDifficulty::Fearless,
+            4 => StygianDifficulty::Dire,
+            5 => StygianDifficulty::Bane,
+            6 => StygianDifficulty::Lethal,
+            index => StygianDifficulty::Other(index),
+        }
+    }
+}
+
+impl std::fmt::Display for StygianDifficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StygianDifficulty::Other(index) => write!(f, "Difficulty {index}"),
+            difficulty => std::fmt::Debug::fmt(difficulty, f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImaginariumTheater {
+    pub difficulty: TheaterDifficulty,
+    /// Last act reached, out of [`TheaterDifficulty::acts`]
+    pub act: u8,
+    /// Stars collected over the whole season, `None` when none were
+    pub stars: Option<u8>,
+}
+
+impl ImaginariumTheater {
+    /// Whether every act of [`Self::difficulty`] was reached, `None` for unknown difficulties
+    pub fn is_complete(&self) -> Option<bool> {
+        self.difficulty.acts().map(|acts| self.act >= acts)
+    }
+}
+
+impl std::fmt::Display for ImaginariumTheater {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} Act {}", self.difficulty, self.act)?;
+        if let Some(acts) = self.difficulty.acts() {
+            write!(f, "/{acts}")?;
+        }
+        if let Some(stars) = self.stars {
+            write!(f, " ({stars}★)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TheaterDifficulty {
+    Easy,
+    Normal,
+    Hard,
+    Visionary,
+    /// Index not known to this version of the crate
+    Other(u8),
+}
+
+impl TheaterDifficulty {
+    /// Index as reported in [`PlayerInfo::theater_mode_index`], starting at 1
+    pub fn index(&self) -> u8 {
+        match self {
+            TheaterDifficulty::Easy => 1,
+            TheaterDifficulty::Normal => 2,
+            TheaterDifficulty::Hard => 3,
+            TheaterDifficulty::Visionary => 4,
+            TheaterDifficulty::Other(index) => *index,
+        }
+    }
+
+    /// Number of acts in a season at this difficulty
+    pub fn acts(&self) -> Option<u8> {
+        match self {
+            TheaterDifficulty::Easy => Some(3),
+            TheaterDifficulty::Normal => Some(6),
+            TheaterDifficulty::Hard => Some(8),
+            TheaterDifficulty::Visionary => Some(10),
+            TheaterDifficulty::Other(_) => None,
+        }
+    }
+}
+
+impl From<u8> for TheaterDifficulty {
+    fn from(index: u8) -> Self {
+        match index {
+            1 => TheaterDifficulty::Easy,
+            2 => TheaterDifficulty::Normal,
+            3 => TheaterDifficulty::Hard,
+            4 => TheaterDifficulty::Visionary,
+            index => TheaterDifficulty::Other(index),
+        }
+    }
+}
+
+impl std::fmt::Display for TheaterDifficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TheaterDifficulty::Other(index) => write!(f, "Difficulty {index}"),
+            difficulty => std::fmt::Debug::fmt(difficulty, f),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(untagged, deny_unknown_fields)]
 pub enum ProfilePicture {
     #[serde(rename_all = "camelCase")]
@@ -241,7 +1085,7 @@ pub enum ProfilePicture {
     Id { id: ProfilePictureId },
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct ShowAvatarInfo {
     pub avatar_id: AvatarId,
@@ -251,7 +1095,16 @@ pub struct ShowAvatarInfo {
     pub talent_level: Option<u8>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// Level cap of characters and weapons at the ascension phase `ascension`
+fn max_level(ascension: u8) -> u8 {
+    match ascension {
+        0 => 20,
+        1 => 40,
+        v => 30 + v * 10,
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct AvatarInfo {
     pub avatar_id: AvatarId,
@@ -268,13 +1121,121 @@ pub struct AvatarInfo {
     pub weapon_skin_id: Option<u64>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl AvatarInfo {
+    pub fn level(&self) -> Option<u8> {
+        self.prop(&Prop::Level)
+    }
+
+    /// Ascension phase, 0 when not ascended yet since the game omits the value then
+    pub fn ascension(&self) -> u8 {
+        self.prop(&Prop::Ascension).unwrap_or(0)
+    }
+
+    /// Level cap at the current [`Self::ascension`]
+    pub fn max_level(&self) -> u8 {
+        max_level(self.ascension())
+    }
+
+    fn prop<T: std::str::FromStr>(&self, prop: &Prop) -> Option<T> {
+        self.prop_map.get(prop)?.val.as_deref()?.parse().ok()
+    }
+
+    /// See [`AvatarId::traveler_element`]
+    pub fn traveler_element(&self) -> Option<Element> {
+        self.avatar_id.traveler_element(self.skill_depot_id)
+    }
+
+    /// Equipped weapon, absent only from malformed showcases
+    pub fn weapon(&self) -> Option<&EquipWeapon> {
+        self.equip_list.iter().find_map(|v| match v {
+            Equip::Weapon(v) => Some(v),
+            Equip::Reliquary(_) => None,
+        })
+    }
+
+    /// Equipped artifacts, in the order of [`Self::equip_list`]
+    pub fn artifacts(&self) -> impl Iterator<Item = &EquipReliquary> + Clone {
+        self.equip_list.iter().filter_map(|v| match v {
+            Equip::Reliquary(v) => Some(v),
+            Equip::Weapon(_) => None,
+        })
+    }
+
+    /// Number of unlocked constellations, 0 to 6
+    pub fn constellation(&self) -> u8 {
+        self.talent_id_list.as_ref().map_or(0, |v| v.len() as u8)
+    }
+
+    /// Normal attack, elemental skill and elemental burst levels, `None` if the character is
+    /// missing from `characters` or its skills are not all present in [`Self::skill_level_map`]
+    ///
+    /// The skill order and proud skill groups come from [`super::store::Character`].
+    pub fn talents(&self, characters: &super::store::Characters) -> Option<Talents> {
+        let character = characters.get(self.avatar_id, self.skill_depot_id)?;
+        let talent = |id: SkillId| {
+            let base = *self.skill_level_map.get(&id)? as u8;
+            let extra = character
+                .proud_map
+                .get(&id)
+                .and_then(|group| self.proud_skill_extra_level_map.as_ref()?.get(group))
+                .map_or(0, |&v| v as u8);
+            Some(Talent { id, base, extra })
+        };
+        let [normal_attack, skill, burst] = character.skill_order.as_slice() else {
+            return None;
+        };
+        Some(Talents {
+            normal_attack: talent(*normal_attack)?,
+            skill: talent(*skill)?,
+            burst: talent(*burst)?,
+        })
+    }
+}
+
+/// See [`AvatarInfo::talents`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Talents {
+    pub normal_attack: Talent,
+    pub skill: Talent,
+    pub burst: Talent,
+}
+
+/// Boosted levels separated by slashes, e.g. `9/10/13`
+impl std::fmt::Display for Talents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            self.normal_attack.level(),
+            self.skill.level(),
+            self.burst.level()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Talent {
+    pub id: SkillId,
+    /// Level the talent was upgraded to
+    pub base: u8,
+    /// Levels granted by constellations and passives
+    pub extra: u8,
+}
+
+impl Talent {
+    /// Effective level, [`Self::base`] plus [`Self::extra`]
+    pub fn level(&self) -> u8 {
+        self.base + self.extra
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct AvatarInfoFetterInfo {
     pub exp_level: u8,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct PropMap {
     pub r#type: Prop,
@@ -283,7 +1244,7 @@ pub struct PropMap {
 }
 
 #[non_exhaustive]
-#[derive(Deserialize_repr, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Deserialize_repr, Serialize_repr, Debug, Clone, PartialEq, Eq, Hash)]
 #[repr(u32)]
 pub enum Prop {
     // TODO: choose nomenclature
@@ -348,8 +1309,8 @@ pub enum Prop {
     // PROP_CUR_PERSIST_DIVE_STAMINA = 10050
 }
 
-#[derive(/* Deserialize, */ Debug, Clone)]
-// #[serde(untagged)]
+#[derive(/* Deserialize, */ Serialize, Debug, Clone)]
+#[serde(untagged)]
 pub enum Equip {
     Weapon(EquipWeapon),
     Reliquary(EquipReliquary),
@@ -370,7 +1331,7 @@ impl<'de> Deserialize<'de> for Equip {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct EquipWeapon {
     pub item_id: ItemId,
@@ -378,7 +1339,96 @@ pub struct EquipWeapon {
     pub flat: FlatWeapon,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl EquipWeapon {
+    /// Refinement rank, 1 to 5
+    pub fn refinement(&self) -> u8 {
+        // the only value of the map is the rank of the passive, starting at 0
+        self.weapon
+            .affix_map
+            .as_ref()
+            .and_then(|v| v.values().next())
+            .map_or(1, |v| *v as u8 + 1)
+    }
+
+    /// Ascension phase, 0 when not ascended yet since the game omits the value then
+    pub fn ascension(&self) -> u8 {
+        self.weapon.promote_level.unwrap_or(0)
+    }
+
+    /// Level cap at the current [`Self::ascension`]
+    pub fn max_level(&self) -> u8 {
+        max_level(self.ascension())
+    }
+
+    /// Star rating, 1 to 5
+    pub fn rarity(&self) -> u8 {
+        self.flat.rank_level
+    }
+
+    /// Guessed from [`Self::item_id`], whose thousands are `11` for swords, `12` for claymores,
+    /// `13` for polearms, `14` for catalysts and `15` for bows for every weapon released so far
+    ///
+    /// `None` is returned for IDs outside these ranges. The type of an equipped weapon is also known
+    /// from its wielder, see [`super::store::Character::weapon_type`].
+    pub fn weapon_type(&self) -> Option<WeaponType> {
+        match self.item_id / 1000 {
+            11 => Some(WeaponType::Sword),
+            12 => Some(WeaponType::Claymore),
+            13 => Some(WeaponType::Polearm),
+            14 => Some(WeaponType::Catalyst),
+            15 => Some(WeaponType::Bow),
+            _ => None,
+        }
+    }
+
+    /// Base ATK at the current level and ascension
+    pub fn base_atk(&self) -> Option<f64> {
+        self.flat
+            .weapon_stats
+            .iter()
+            .find(|v| v.append_prop_id == BASE_ATTACK)
+            .map(|v| v.stat_value)
+    }
+
+    /// Substat at the current level, absent on low rarity weapons
+    pub fn secondary_stat(&self) -> Option<&WeaponStat> {
+        self.flat
+            .weapon_stats
+            .iter()
+            .find(|v| v.append_prop_id != BASE_ATTACK)
+    }
+}
+
+const BASE_ATTACK: &str = "FIGHT_PROP_BASE_ATTACK";
+
+/// `R1 Lv90/90`
+impl std::fmt::Display for EquipWeapon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "R{} Lv{}/{}",
+            self.refinement(),
+            self.weapon.level,
+            self.max_level()
+        )
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WeaponType {
+    #[serde(rename = "WEAPON_SWORD_ONE_HAND")]
+    Sword,
+    #[serde(rename = "WEAPON_CLAYMORE")]
+    Claymore,
+    #[serde(rename = "WEAPON_POLE")]
+    Polearm,
+    #[serde(rename = "WEAPON_CATALYST")]
+    Catalyst,
+    #[serde(rename = "WEAPON_BOW")]
+    Bow,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct EquipReliquary {
     pub item_id: ItemId,
@@ -386,16 +1436,18 @@ pub struct EquipReliquary {
     pub flat: FlatReliquary,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Weapon {
     pub level: u8,
+    /// See [`EquipWeapon::ascension`]
     pub promote_level: Option<u8>,
-    pub affix_map: Option<HashMap<u64, u64>>, // see equip.rs
+    /// Passive affix ID to refinement rank starting at 0, see [`EquipWeapon::refinement`]
+    pub affix_map: Option<HashMap<u64, u64>>,
     pub exp: Option<u64>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Reliquary {
     pub level: u8,
@@ -407,7 +1459,7 @@ pub struct Reliquary {
 type MainPropId = u32;
 type AppendPropId = u32;
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct FlatWeapon {
     pub name_text_map_hash: TextMapHash,
@@ -417,7 +1469,7 @@ pub struct FlatWeapon {
     pub weapon_stats: Vec<WeaponStat>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct FlatReliquary {
     pub name_text_map_hash: TextMapHash,
@@ -428,31 +1480,501 @@ pub struct FlatReliquary {
     pub set_id: Option<u64>,
     pub item_type: String,
     pub icon: String,
-    pub equip_type: String,
+    pub equip_type: EquipType,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// Artifact slot, in in-game order
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum EquipType {
     EquipBracer,
-    EquipNeackle,
+    EquipNecklace,
     EquipShoes,
     EquipRing,
     EquipDress,
 }
 
 type GameStat = String; // TODO: unimplemented
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct MainStat {
     pub main_prop_id: GameStat,
     pub stat_value: f64,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct SubStat {
     pub append_prop_id: GameStat,
     pub stat_value: f64,
 }
 type WeaponStat = SubStat;
+
+impl MainStat {
+    pub fn prop(&self) -> Option<FightProp> {
+        self.main_prop_id.parse().ok()
+    }
+}
+
+impl SubStat {
+    pub fn prop(&self) -> Option<FightProp> {
+        self.append_prop_id.parse().ok()
+    }
+}
+
+macro_rules! fight_props {
+    ($($variant:ident = $id:literal, $name:literal;)*) => {
+        /// Keys of [`AvatarInfo::fight_prop_map`], also used by name in [`MainStat`] and
+        /// [`SubStat`]
+        #[non_exhaustive]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[repr(u32)]
+        pub enum FightProp {
+            $($variant = $id,)*
+        }
+
+        impl FightProp {
+            pub fn from_id(id: u32) -> Option<Self> {
+                match id {
+                    $($id => Some(FightProp::$variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn id(self) -> u32 {
+                self as u32
+            }
+
+            /// Name used by the game, e.g. `FIGHT_PROP_CRITICAL`
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(FightProp::$variant => $name,)*
+                }
+            }
+        }
+
+        impl std::str::FromStr for FightProp {
+            type Err = UnknownFightProp;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($name => Ok(FightProp::$variant),)*
+                    _ => Err(UnknownFightProp(s.to_owned())),
+                }
+            }
+        }
+    };
+}
+
+fight_props! {
+    BaseHp = 1, "FIGHT_PROP_BASE_HP";
+    Hp = 2, "FIGHT_PROP_HP";
+    HpPercent = 3, "FIGHT_PROP_HP_PERCENT";
+    BaseAttack = 4, "FIGHT_PROP_BASE_ATTACK";
+    Attack = 5, "FIGHT_PROP_ATTACK";
+    AttackPercent = 6, "FIGHT_PROP_ATTACK_PERCENT";
+    BaseDefense = 7, "FIGHT_PROP_BASE_DEFENSE";
+    Defense = 8, "FIGHT_PROP_DEFENSE";
+    DefensePercent = 9, "FIGHT_PROP_DEFENSE_PERCENT";
+    Critical = 20, "FIGHT_PROP_CRITICAL";
+    CriticalHurt = 22, "FIGHT_PROP_CRITICAL_HURT";
+    ChargeEfficiency = 23, "FIGHT_PROP_CHARGE_EFFICIENCY";
+    HealAdd = 26, "FIGHT_PROP_HEAL_ADD";
+    HealedAdd = 27, "FIGHT_PROP_HEALED_ADD";
+    ElementMastery = 28, "FIGHT_PROP_ELEMENT_MASTERY";
+    PhysicalSubHurt = 29, "FIGHT_PROP_PHYSICAL_SUB_HURT";
+    PhysicalAddHurt = 30, "FIGHT_PROP_PHYSICAL_ADD_HURT";
+    FireAddHurt = 40, "FIGHT_PROP_FIRE_ADD_HURT";
+    ElecAddHurt = 41, "FIGHT_PROP_ELEC_ADD_HURT";
+    WaterAddHurt = 42, "FIGHT_PROP_WATER_ADD_HURT";
+    GrassAddHurt = 43, "FIGHT_PROP_GRASS_ADD_HURT";
+    WindAddHurt = 44, "FIGHT_PROP_WIND_ADD_HURT";
+    RockAddHurt = 45, "FIGHT_PROP_ROCK_ADD_HURT";
+    IceAddHurt = 46, "FIGHT_PROP_ICE_ADD_HURT";
+    FireSubHurt = 50, "FIGHT_PROP_FIRE_SUB_HURT";
+    ElecSubHurt = 51, "FIGHT_PROP_ELEC_SUB_HURT";
+    WaterSubHurt = 52, "FIGHT_PROP_WATER_SUB_HURT";
+    GrassSubHurt = 53, "FIGHT_PROP_GRASS_SUB_HURT";
+    WindSubHurt = 54, "FIGHT_PROP_WIND_SUB_HURT";
+    RockSubHurt = 55, "FIGHT_PROP_ROCK_SUB_HURT";
+    IceSubHurt = 56, "FIGHT_PROP_ICE_SUB_HURT";
+    MaxHp = 2000, "FIGHT_PROP_MAX_HP";
+    CurAttack = 2001, "FIGHT_PROP_CUR_ATTACK";
+    CurDefense = 2002, "FIGHT_PROP_CUR_DEFENSE";
+}
+
+impl FightProp {
+    /// Whether the value is a ratio, stored as such in [`AvatarInfo::fight_prop_map`] but as a
+    /// percentage in [`MainStat::stat_value`] and [`SubStat::stat_value`]
+    pub fn is_percent(self) -> bool {
+        !matches!(
+            self,
+            FightProp::BaseHp
+                | FightProp::Hp
+                | FightProp::BaseAttack
+                | FightProp::Attack
+                | FightProp::BaseDefense
+                | FightProp::Defense
+                | FightProp::ElementMastery
+                | FightProp::MaxHp
+                | FightProp::CurAttack
+                | FightProp::CurDefense
+        )
+    }
+
+    /// Short English name, e.g. `Crit Rate`
+    pub fn label(self) -> &'static str {
+        match self {
+            FightProp::BaseHp => "Base HP",
+            FightProp::Hp | FightProp::MaxHp => "HP",
+            FightProp::HpPercent => "HP%",
+            FightProp::BaseAttack => "Base ATK",
+            FightProp::Attack | FightProp::CurAttack => "ATK",
+            FightProp::AttackPercent => "ATK%",
+            FightProp::BaseDefense => "Base DEF",
+            FightProp::Defense | FightProp::CurDefense => "DEF",
+            FightProp::DefensePercent => "DEF%",
+            FightProp::Critical => "Crit Rate",
+            FightProp::CriticalHurt => "Crit DMG",
+            FightProp::ChargeEfficiency => "Energy Recharge",
+            FightProp::HealAdd => "Healing Bonus",
+            FightProp::HealedAdd => "Incoming Healing Bonus",
+            FightProp::ElementMastery => "Elemental Mastery",
+            FightProp::PhysicalSubHurt => "Physical RES",
+            FightProp::PhysicalAddHurt => "Physical DMG Bonus",
+            FightProp::FireAddHurt => "Pyro DMG Bonus",
+            FightProp::ElecAddHurt => "Electro DMG Bonus",
+            FightProp::WaterAddHurt => "Hydro DMG Bonus",
+            FightProp::GrassAddHurt => "Dendro DMG Bonus",
+            FightProp::WindAddHurt => "Anemo DMG Bonus",
+            FightProp::RockAddHurt => "Geo DMG Bonus",
+            FightProp::IceAddHurt => "Cryo DMG Bonus",
+            FightProp::FireSubHurt => "Pyro RES",
+            FightProp::ElecSubHurt => "Electro RES",
+            FightProp::WaterSubHurt => "Hydro RES",
+            FightProp::GrassSubHurt => "Dendro RES",
+            FightProp::WindSubHurt => "Anemo RES",
+            FightProp::RockSubHurt => "Geo RES",
+            FightProp::IceSubHurt => "Cryo RES",
+        }
+    }
+
+    /// Elemental DMG bonus of `element`
+    pub fn damage_bonus(element: Element) -> Self {
+        match element {
+            Element::Pyro => FightProp::FireAddHurt,
+            Element::Hydro => FightProp::WaterAddHurt,
+            Element::Anemo => FightProp::WindAddHurt,
+            Element::Electro => FightProp::ElecAddHurt,
+            Element::Dendro => FightProp::GrassAddHurt,
+            Element::Cryo => FightProp::IceAddHurt,
+            Element::Geo => FightProp::RockAddHurt,
+        }
+    }
+}
+
+impl std::fmt::Display for FightProp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Serialize for FightProp {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for FightProp {
+    fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Unknown fight prop {0:?}")]
+pub struct UnknownFightProp(pub String);
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Parses a file of `tests/fixtures`
+    pub(crate) fn fixture<T: serde::de::DeserializeOwned>(name: &str) -> T {
+        let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn endgame_progress() {
+        let response = fixture::<player::Response>("player.json");
+        let player = &response.info.player_info;
+        let abyss = player.spiral_abyss().unwrap();
+        assert_eq!((abyss.floor, abyss.chamber, abyss.stars), (12, 3, Some(36)));
+        assert_eq!(abyss.to_string(), "12-3 (36★)");
+        let stygian = player.stygian_onslaught().unwrap();
+        assert_eq!(stygian.season, Some(2));
+        assert_eq!(stygian.difficulty, StygianDifficulty::Lethal);
+        assert_eq!(stygian.difficulty.to_string(), "Lethal");
+        assert_eq!(StygianDifficulty::from(3).index(), 3);
+        assert_eq!(StygianDifficulty::from(7).to_string(), "Difficulty 7");
+        assert_eq!(
+            stygian.clear_time,
+            Some(std::time::Duration::from_secs(113))
+        );
+        let theater = player.imaginarium_theater().unwrap();
+        assert_eq!(theater.difficulty, TheaterDifficulty::Visionary);
+        assert_eq!(theater.is_complete(), Some(true));
+        assert_eq!(theater.to_string(), "Visionary Act 10/10 (10★)");
+
+        let mut player = player.clone();
+        player.tower_floor_index = None;
+        player.stygian_index = None;
+        player.theater_mode_index = None;
+        assert!(player.spiral_abyss().is_none());
+        assert!(player.stygian_onslaught().is_none());
+        assert!(player.imaginarium_theater().is_none());
+    }
+
+    #[test]
+    fn talents() {
+        let avatar = fixture::<AvatarInfo>("avatar.json");
+        let characters = fixture::<crate::gi::store::Characters>("characters.json");
+        let talents = avatar.talents(&characters).unwrap();
+        assert_eq!(
+            (
+                talents.skill.base,
+                talents.skill.extra,
+                talents.skill.level()
+            ),
+            (10, 3, 13)
+        );
+        assert_eq!(talents.burst.extra, 0);
+        assert_eq!(
+            format!("C{} · {talents}", avatar.constellation()),
+            "C3 · 9/13/10"
+        );
+        assert!(avatar.talents(&Default::default()).is_none());
+    }
+
+    #[test]
+    fn weapon() {
+        let avatar = fixture::<AvatarInfo>("avatar.json");
+        let mut weapon = avatar.weapon().unwrap().clone();
+        assert_eq!(weapon.refinement(), 1);
+        assert_eq!(weapon.max_level(), 90);
+        assert_eq!(weapon.weapon_type(), Some(WeaponType::Polearm));
+        assert_eq!(weapon.base_atk(), Some(608.0));
+        assert_eq!(weapon.to_string(), "R1 Lv90/90");
+
+        weapon.weapon.affix_map = Some(HashMap::from([(113501, 4)]));
+        assert_eq!(weapon.refinement(), 5);
+        weapon.weapon.affix_map = None;
+        assert_eq!(weapon.refinement(), 1);
+
+        for (promote_level, max_level) in [(None, 20), (Some(1), 40), (Some(2), 50), (Some(5), 80)]
+        {
+            weapon.weapon.promote_level = promote_level;
+            assert_eq!(weapon.max_level(), max_level);
+        }
+
+        weapon.item_id = 11401;
+        assert_eq!(weapon.weapon_type(), Some(WeaponType::Sword));
+        weapon.item_id = 10002;
+        assert_eq!(weapon.weapon_type(), None);
+    }
+
+    fn build(settings: serde_json::Value) -> profile::hoyo::build::Build {
+        serde_json::from_value(serde_json::json!({
+            "id": 1830039,
+            "name": "Vaporize",
+            "avatar_id": "10000046",
+            "avatar_data": fixture::<serde_json::Value>("avatar.json"),
+            "order": 0,
+            "live": false,
+            "settings": settings,
+            "public": true,
+            "image": null,
+            "hoyo_type": 0,
+            "hoyo": "4Wjv2e",
+            "owner": "Algoinde",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn build_settings() {
+        use profile::hoyo::build::{ArtSource, Transform};
+        let settings = build(serde_json::json!({})).settings;
+        assert!(settings.art_source.is_none() && settings.transform.is_none());
+
+        let settings = build(serde_json::json!({
+            "adaptiveColor": true,
+            "artSource": "gacha",
+            "caption": "Vape",
+            "honkardWidth": 520.5,
+            "transform": { "x": -12.5, "y": 30, "scale": 1.25, "rotation": 5, "flip": true },
+        }))
+        .settings;
+        assert_eq!(settings.adaptive_color, Some(true));
+        assert_eq!(settings.art_source, Some(ArtSource::Gacha));
+        assert_eq!(settings.caption.as_deref(), Some("Vape"));
+        assert_eq!(settings.honkard_width, Some(520.5));
+        assert_eq!(
+            settings.transform,
+            Some(Transform {
+                x: -12.5,
+                y: 30.0,
+                scale: 1.25,
+                rotation: 5.0,
+                flip: true,
+                extra: HashMap::new(),
+            })
+        );
+
+        let settings = build(serde_json::json!({
+            "artSource": "anime",
+            "transform": { "scale": 2, "skew": 3 },
+        }))
+        .settings;
+        assert_eq!(
+            settings.art_source,
+            Some(ArtSource::Other("anime".to_owned()))
+        );
+        let transform = settings.transform.unwrap();
+        assert_eq!((transform.x, transform.scale), (0.0, 2.0));
+        assert_eq!(transform.extra["skew"], 3);
+    }
+
+    #[test]
+    fn uid_length() {
+        assert_eq!(Uid::new(0), Err(UidError::Length));
+        assert_eq!(Uid::new(2_000_000_000), Err(UidError::Length));
+        assert_eq!(Uid::new(618_285_856).map(|v| v.get()), Ok(618_285_856));
+        assert_eq!(
+            Uid::new(1_812_345_678).map(|v| v.region()),
+            Ok(Some(profile::hoyo::Region::Asia))
+        );
+        let internal = Uid::new(12_345_678).unwrap();
+        assert_eq!(internal.region(), Some(profile::hoyo::Region::Internal));
+        assert_eq!(internal.to_string(), "012345678");
+        assert_eq!(Uid::new(412_345_678), Err(UidError::Region));
+    }
+
+    #[test]
+    fn uid_from_str() {
+        assert_eq!("123".parse::<Uid>(), Err(UidError::Length));
+        assert_eq!("000000000".parse::<Uid>(), Err(UidError::Length));
+        assert_eq!(
+            "000000123".parse::<Uid>().map(|v| v.to_string()),
+            Ok("000000123".to_owned())
+        );
+        assert_eq!("2812345678".parse::<Uid>(), Err(UidError::Length));
+        assert_eq!("61828585a".parse::<Uid>(), Err(UidError::NotANumber));
+        let uid = "618285856".parse::<Uid>().unwrap();
+        assert_eq!(uid.region(), Some(profile::hoyo::Region::America));
+        assert_eq!(uid.to_string(), "618285856");
+        assert_eq!(
+            "1812345678".parse::<Uid>().map(|v| v.to_string()),
+            Ok("1812345678".to_owned())
+        );
+    }
+
+    #[test]
+    fn uid_deserialize() {
+        let uid = serde_json::from_str::<Uid>("\"412345678\"").unwrap();
+        assert_eq!((uid.get(), uid.region()), (412_345_678, None));
+        let uid = serde_json::from_str::<Uid>("12345678").unwrap();
+        assert_eq!(uid.region(), Some(profile::hoyo::Region::Internal));
+        assert!(serde_json::from_str::<Uid>("\"61828585a\"").is_err());
+    }
+
+    #[test]
+    fn builds() {
+        use profile::hoyo::builds::Builds;
+        let hu_tao = AvatarId::from(10000046);
+        let diluc = AvatarId::from(10000016);
+        let entry = |id, name: &str, order, live, public| {
+            let mut build = build(serde_json::json!({}));
+            (build.id, build.order, build.live, build.public) = (id, order, live, public);
+            build.name = name.to_owned();
+            build
+        };
+        let builds = Builds(HashMap::from([
+            (
+                hu_tao,
+                vec![
+                    entry(5, "", Order::Index(0), true, true),
+                    entry(3, "Melt", Order::Key("b".to_owned()), false, true),
+                    entry(7, "", Order::Index(0), true, true),
+                    entry(2, "Double Geo", Order::Index(1), false, false),
+                    entry(4, "Vaporize", Order::Index(0), false, true),
+                ],
+            ),
+            (
+                diluc,
+                vec![entry(9, "Vaporize", Order::Index(0), false, false)],
+            ),
+        ]));
+        let ids = |builds: &mut dyn Iterator<Item = &profile::hoyo::build::Build>| {
+            builds.map(|v| v.id).collect::<Vec<_>>()
+        };
+        assert_eq!(builds.live(hu_tao).map(|v| v.id), Some(7));
+        assert_eq!(builds.live(diluc).map(|v| v.id), None);
+        assert_eq!(ids(&mut builds.saved(hu_tao)), [4, 2, 3]);
+        assert_eq!(ids(&mut builds.iter_ordered_for(hu_tao)), [4, 5, 7, 2, 3]);
+        let mut named = ids(&mut builds.named("Vaporize"));
+        named.sort_unstable();
+        assert_eq!(named, [4, 9]);
+        assert_eq!(
+            builds
+                .iter_by_avatar()
+                .map(|(id, builds)| (id, builds.count()))
+                .collect::<Vec<_>>(),
+            [(diluc, 1), (hu_tao, 5)]
+        );
+
+        let builds = builds.public_only();
+        assert!(!builds.contains_key(&diluc));
+        assert_eq!(ids(&mut builds.saved(hu_tao)), [4, 3]);
+    }
+
+    #[test]
+    fn order() {
+        let order = |v| serde_json::from_value::<Order>(v).unwrap();
+        assert_eq!(order(serde_json::json!(3)), Order::Index(3));
+        assert_eq!(order(serde_json::json!("12")), Order::Index(12));
+        assert_eq!(order(serde_json::json!("a0")), Order::Key("a0".to_owned()));
+        assert!(serde_json::from_value::<Order>(serde_json::json!(-1)).is_err());
+
+        let mut orders = ["b", "10", "a0", "2", "a"].map(|v| order(serde_json::json!(v)));
+        orders.sort();
+        assert_eq!(
+            orders,
+            [
+                Order::Index(2),
+                Order::Index(10),
+                Order::Key("a".to_owned()),
+                Order::Key("a0".to_owned()),
+                Order::Key("b".to_owned()),
+            ]
+        );
+        assert!(Order::Index(u64::MAX) < Order::Key(String::new()));
+
+        let hoyos = serde_json::from_value::<profile::hoyos::Hoyos>(serde_json::json!({
+            "unordered": { "hoyo_type": 1 },
+            "key": { "hoyo_type": 2, "order": "b" },
+            "index": { "hoyo_type": 1, "order": 1 },
+        }))
+        .unwrap();
+        assert_eq!(
+            hoyos
+                .iter_ordered()
+                .map(|(hash, _)| hash.as_str())
+                .collect::<Vec<_>>(),
+            ["index", "key", "unordered"]
+        );
+    }
+}
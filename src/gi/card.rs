@@ -0,0 +1,507 @@
+//! Rendering of character build cards, to SVG and with the `card-png` feature to PNG
+//!
+//! Images are read from a local copy of the enka.network UI assets through [`Assets`], so that
+//! cards can be rendered offline. Missing images are left out of the card.
+use super::{
+    api::{
+        AvatarInfo, Element, EquipReliquary, EquipType, EquipWeapon, FightProp,
+        profile::hoyo::build::{ArtSource, Build, Settings, Transform},
+    },
+    calc::Stats,
+    store::{Character, Characters, Localizations},
+};
+use base64::Engine;
+use std::{fmt::Write, path::PathBuf};
+
+const WIDTH: f64 = 1200.0;
+const HEIGHT: f64 = 600.0;
+
+/// Source of the images drawn on cards, looked up by their enka.network asset name, e.g.
+/// `UI_Gacha_AvatarImg_Hutao`
+pub trait Assets {
+    /// PNG data of the image
+    fn image(&self, name: &str) -> Option<Vec<u8>>;
+}
+
+/// Directory holding `{name}.png` files, as downloaded from `https://enka.network/ui/{name}.png`
+impl Assets for PathBuf {
+    fn image(&self, name: &str) -> Option<Vec<u8>> {
+        std::fs::read(self.join(format!("{name}.png"))).ok()
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[cfg(feature = "card-png")]
+pub enum Error {
+    #[error("Failed to parse rendered SVG")]
+    Svg(#[from] resvg::usvg::Error),
+    #[error("Failed to encode PNG")]
+    Png(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Card of a single character
+pub struct Card<'a> {
+    avatar: &'a AvatarInfo,
+    characters: &'a Characters,
+    localizations: &'a Localizations,
+    assets: &'a dyn Assets,
+    settings: Option<&'a Settings>,
+    /// Language of the names, `en` by default
+    pub language: String,
+}
+
+impl std::fmt::Debug for Card<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Card")
+            .field("avatar_id", &self.avatar.avatar_id)
+            .field("settings", &self.settings)
+            .field("language", &self.language)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Card<'a> {
+    pub fn new(
+        avatar: &'a AvatarInfo,
+        characters: &'a Characters,
+        localizations: &'a Localizations,
+        assets: &'a dyn Assets,
+    ) -> Self {
+        Card {
+            avatar,
+            characters,
+            localizations,
+            assets,
+            settings: None,
+            language: "en".to_owned(),
+        }
+    }
+
+    /// Card of a saved build, laid out with the user's [`Settings`]
+    pub fn from_build(
+        build: &'a Build,
+        characters: &'a Characters,
+        localizations: &'a Localizations,
+        assets: &'a dyn Assets,
+    ) -> Self {
+        Card {
+            settings: Some(&build.settings),
+            ..Card::new(&build.avatar_data, characters, localizations, assets)
+        }
+    }
+
+    fn character(&self) -> Option<&'a Character> {
+        self.characters
+            .get(self.avatar.avatar_id, self.avatar.skill_depot_id)
+    }
+
+    fn name(&self, hash: impl std::fmt::Display) -> String {
+        self.localizations
+            .get(&self.language, &hash)
+            .map_or_else(|| hash.to_string(), str::to_owned)
+    }
+
+    fn element(&self) -> Option<Element> {
+        self.avatar.traveler_element().or_else(|| {
+            Some(match self.character()?.element.as_deref()? {
+                "Fire" => Element::Pyro,
+                "Water" => Element::Hydro,
+                "Wind" => Element::Anemo,
+                "Electric" => Element::Electro,
+                "Grass" => Element::Dendro,
+                "Ice" => Element::Cryo,
+                "Rock" => Element::Geo,
+                _ => return None,
+            })
+        })
+    }
+
+    /// `<image>` of the asset `name`, nothing if missing
+    fn image(&self, name: &str, x: f64, y: f64, size: f64, opacity: f64) -> String {
+        match self.assets.image(name) {
+            Some(data) => format!(
+                r#"<image x="{x}" y="{y}" width="{size}" height="{size}" opacity="{opacity}" href="data:image/png;base64,{}"/>"#,
+                base64::engine::general_purpose::STANDARD.encode(data)
+            ),
+            None => String::new(),
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let accent = match self.settings.and_then(|v| v.adaptive_color) {
+            Some(false) => None,
+            _ => self.element(),
+        }
+        .map_or("#5a5a6e", color);
+        let art_width = self
+            .settings
+            .and_then(|v| v.honkard_width)
+            .unwrap_or(420.0)
+            .clamp(200.0, 700.0);
+        let mut svg = format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif" fill="#fff">"##
+        );
+        let _ = write!(
+            svg,
+            r##"<defs><linearGradient id="bg" x1="0" y1="0" x2="1" y2="1"><stop offset="0" stop-color="{accent}"/><stop offset="1" stop-color="#1c1c24"/></linearGradient><clipPath id="art"><rect width="{art_width}" height="{HEIGHT}"/></clipPath></defs><rect width="{WIDTH}" height="{HEIGHT}" rx="16" fill="url(#bg)"/>"##
+        );
+        svg += &self.art(art_width);
+        let x = art_width + 24.0;
+        svg += &self.header(x);
+        svg += &self.stats(x);
+        svg += &self.weapon(x + 340.0);
+        svg += &self.talents(x + 340.0);
+        svg += &self.artifacts(x);
+        svg += "</svg>";
+        svg
+    }
+
+    /// Asset of the character art: the splash art of the worn costume when asked for and known,
+    /// the wish art otherwise
+    fn art_name(&self) -> Option<String> {
+        let character = self.character()?;
+        if let Some(ArtSource::Splash) = self.settings.and_then(|v| v.art_source.as_ref()) {
+            let costume = self
+                .avatar
+                .costume_id
+                .and_then(|v| character.costumes.get(&v))
+                .and_then(|v| v.art.clone());
+            if costume.is_some() {
+                return costume;
+            }
+        }
+        Some(
+            character
+                .side_icon_name
+                .as_deref()?
+                .replace("UI_AvatarIcon_Side_", "UI_Gacha_AvatarImg_"),
+        )
+    }
+
+    fn art(&self, width: f64) -> String {
+        let Some(name) = self.art_name() else {
+            return String::new();
+        };
+        let default = Transform::default();
+        let t = self
+            .settings
+            .and_then(|v| v.transform.as_ref())
+            .unwrap_or(&default);
+        let size = HEIGHT * t.scale;
+        let center = (width / 2.0, HEIGHT / 2.0);
+        format!(
+            r#"<g clip-path="url(#art)"><g transform="translate({} {}) rotate({}) scale({} 1) translate({} {})">{}</g></g>"#,
+            center.0 + t.x,
+            center.1 + t.y,
+            t.rotation,
+            if t.flip { -1 } else { 1 },
+            -size / 2.0,
+            -size / 2.0,
+            self.image(&name, 0.0, 0.0, size, 1.0),
+        )
+    }
+
+    fn header(&self, x: f64) -> String {
+        let name = self
+            .character()
+            .and_then(|v| v.name_text_map_hash)
+            .map_or_else(|| self.avatar.avatar_id.to_string(), |v| self.name(v));
+        let mut summary = format!(
+            "Lv{}/{} · C{}",
+            self.avatar.level().unwrap_or(1),
+            self.avatar.max_level(),
+            self.avatar.constellation()
+        );
+        if let Some(talents) = self.avatar.talents(self.characters) {
+            let _ = write!(summary, " · {talents}");
+        }
+        let mut svg = format!(
+            r#"<text x="{x}" y="60" font-size="36" font-weight="bold">{}</text><text x="{x}" y="92" font-size="20" opacity="0.85">{}</text>"#,
+            escape(&name),
+            escape(&summary),
+        );
+        if let Some(caption) = self.settings.and_then(|v| v.caption.as_deref()) {
+            let _ = write!(
+                svg,
+                r#"<text x="{x}" y="120" font-size="16" font-style="italic" opacity="0.7">{}</text>"#,
+                escape(caption)
+            );
+        }
+        svg
+    }
+
+    fn stats(&self, x: f64) -> String {
+        let stats = Stats::from_avatar(self.avatar);
+        let bonus = self
+            .element()
+            .map_or(FightProp::PhysicalAddHurt, FightProp::damage_bonus);
+        let rows = [
+            (FightProp::MaxHp, stats.hp()),
+            (FightProp::CurAttack, stats.atk()),
+            (FightProp::CurDefense, stats.def()),
+            (
+                FightProp::ElementMastery,
+                stats.get(FightProp::ElementMastery),
+            ),
+            (FightProp::Critical, stats.get(FightProp::Critical)),
+            (FightProp::CriticalHurt, stats.get(FightProp::CriticalHurt)),
+            (
+                FightProp::ChargeEfficiency,
+                stats.get(FightProp::ChargeEfficiency),
+            ),
+            (bonus, stats.get(bonus)),
+        ];
+        let mut svg = String::new();
+        for (i, (prop, value)) in rows.into_iter().enumerate() {
+            let y = 150.0 + i as f64 * 26.0;
+            let _ = write!(
+                svg,
+                r#"<text x="{x}" y="{y}" font-size="17">{}</text><text x="{}" y="{y}" font-size="17" text-anchor="end">{}</text>"#,
+                prop.label(),
+                x + 310.0,
+                format_stat(prop, value, true),
+            );
+        }
+        svg
+    }
+
+    fn weapon(&self, x: f64) -> String {
+        let Some(weapon) = self.avatar.weapon() else {
+            return String::new();
+        };
+        let icon = weapon_icon(weapon);
+        let mut lines = vec![weapon.to_string()];
+        if let Some(atk) = weapon.base_atk() {
+            lines.push(format!("Base ATK {atk:.0}"));
+        }
+        if let Some(stat) = weapon.secondary_stat()
+            && let Some(prop) = stat.prop()
+        {
+            lines.push(format!(
+                "{} {}",
+                prop.label(),
+                format_stat(prop, stat.stat_value, false)
+            ));
+        }
+        let mut svg = self.image(&icon, x, 126.0, 72.0, 1.0);
+        let _ = write!(
+            svg,
+            r#"<text x="{}" y="146" font-size="18" font-weight="bold">{}</text>"#,
+            x + 84.0,
+            escape(&self.name(&weapon.flat.name_text_map_hash)),
+        );
+        for (i, line) in lines.iter().enumerate() {
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="15" opacity="0.85">{}</text>"#,
+                x + 84.0,
+                168.0 + i as f64 * 20.0,
+                escape(line),
+            );
+        }
+        svg
+    }
+
+    fn talents(&self, x: f64) -> String {
+        let Some(character) = self.character() else {
+            return String::new();
+        };
+        let mut svg = String::new();
+        if let Some(talents) = self.avatar.talents(self.characters) {
+            for (i, talent) in [talents.normal_attack, talents.skill, talents.burst]
+                .into_iter()
+                .enumerate()
+            {
+                let x = x + i as f64 * 64.0;
+                if let Some(icon) = character.skills.get(&talent.id) {
+                    svg += &self.image(icon, x, 230.0, 48.0, 1.0);
+                }
+                let _ = write!(
+                    svg,
+                    r#"<text x="{}" y="296" font-size="15" text-anchor="middle"{}>{}</text>"#,
+                    x + 24.0,
+                    if talent.extra > 0 {
+                        r##" fill="#7fdfff""##
+                    } else {
+                        ""
+                    },
+                    talent.level(),
+                );
+            }
+        }
+        let unlocked = usize::from(self.avatar.constellation());
+        for (i, icon) in character.consts.iter().enumerate() {
+            let opacity = if i < unlocked { 1.0 } else { 0.3 };
+            svg += &self.image(icon, x + i as f64 * 44.0, 310.0, 36.0, opacity);
+        }
+        svg
+    }
+
+    fn artifacts(&self, x: f64) -> String {
+        let mut artifacts = self.avatar.artifacts().collect::<Vec<_>>();
+        artifacts.sort_by_key(|v| v.flat.equip_type);
+        let column = (WIDTH - x - 24.0) / 5.0;
+        let mut svg = String::new();
+        for artifact in artifacts {
+            let x = x + slot(artifact.flat.equip_type) as f64 * column;
+            svg += &self.artifact(artifact, x, column - 8.0);
+        }
+        svg
+    }
+
+    fn artifact(&self, artifact: &EquipReliquary, x: f64, width: f64) -> String {
+        let flat = &artifact.flat;
+        let mut svg = format!(
+            r##"<rect x="{x}" y="360" width="{width}" height="220" rx="8" fill="#000" opacity="0.25"/>"##
+        );
+        svg += &self.image(&flat.icon, x + 4.0, 364.0, 56.0, 1.0);
+        if let Some(prop) = flat.reliquary_mainstat.prop() {
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="384" font-size="13" text-anchor="end" opacity="0.8">{}</text><text x="{}" y="408" font-size="20" font-weight="bold" text-anchor="end">{}</text><text x="{}" y="428" font-size="13" text-anchor="end" opacity="0.8">+{}</text>"#,
+                x + width - 6.0,
+                prop.label(),
+                x + width - 6.0,
+                format_stat(prop, flat.reliquary_mainstat.stat_value, false),
+                x + width - 6.0,
+                artifact.reliquary.level.saturating_sub(1),
+            );
+        }
+        for (i, substat) in flat.reliquary_substats.iter().flatten().enumerate() {
+            let Some(prop) = substat.prop() else {
+                continue;
+            };
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="14">{} {}</text>"#,
+                x + 6.0,
+                456.0 + i as f64 * 24.0,
+                prop.label(),
+                format_stat(prop, substat.stat_value, false),
+            );
+        }
+        svg
+    }
+
+    /// Rasterizes [`Self::to_svg`], using the fonts installed on the system
+    #[cfg(feature = "card-png")]
+    pub fn to_png(&self) -> Result<Vec<u8>, Error> {
+        use resvg::{tiny_skia, usvg};
+        let mut options = usvg::Options::default();
+        options.fontdb_mut().load_system_fonts();
+        let tree = usvg::Tree::from_str(&self.to_svg(), &options)?;
+        let mut pixmap = tiny_skia::Pixmap::new(WIDTH as u32, HEIGHT as u32)
+            .expect("card dimensions are not zero");
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+        pixmap.encode_png().map_err(|e| Error::Png(e.into()))
+    }
+}
+
+/// Icon of the weapon, using its ascended art from the second ascension on like in game
+fn weapon_icon(weapon: &EquipWeapon) -> String {
+    if weapon.ascension() >= 2 {
+        format!("{}_Awaken", weapon.flat.icon)
+    } else {
+        weapon.flat.icon.clone()
+    }
+}
+
+fn slot(equip_type: EquipType) -> usize {
+    match equip_type {
+        EquipType::EquipBracer => 0,
+        EquipType::EquipNecklace => 1,
+        EquipType::EquipShoes => 2,
+        EquipType::EquipRing => 3,
+        EquipType::EquipDress => 4,
+    }
+}
+
+/// `ratio` tells whether percentages are stored as ratios, like in
+/// [`AvatarInfo::fight_prop_map`], or as displayed
+fn format_stat(prop: FightProp, value: f64, ratio: bool) -> String {
+    if prop.is_percent() {
+        format!("{:.1}%", if ratio { value * 100.0 } else { value })
+    } else {
+        format!("{value:.0}")
+    }
+}
+
+fn color(element: Element) -> &'static str {
+    match element {
+        Element::Pyro => "#b5472d",
+        Element::Hydro => "#2d6fb5",
+        Element::Anemo => "#2d9c86",
+        Element::Electro => "#7d4ab5",
+        Element::Dendro => "#5c9c2d",
+        Element::Cryo => "#5fa7c4",
+        Element::Geo => "#b5902d",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Assets, Card};
+    use crate::gi::{
+        api::{
+            AvatarInfo,
+            profile::hoyo::build::{ArtSource, Settings},
+            tests::fixture,
+        },
+        store::{Characters, Localizations},
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn dotted_asset_name() {
+        let dir = std::env::temp_dir().join(format!("enka-rs-assets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("UI_Gacha_AvatarImg_Hutao.v2.png"), b"png").unwrap();
+        assert_eq!(
+            dir.image("UI_Gacha_AvatarImg_Hutao.v2").as_deref(),
+            Some(&b"png"[..])
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn art_name() {
+        let mut avatar: AvatarInfo = fixture("avatar.json");
+        let characters: Characters = fixture("characters.json");
+        let localizations = Localizations::default();
+        let assets = PathBuf::new();
+        let settings = Settings {
+            adaptive_color: None,
+            art_source: Some(ArtSource::Splash),
+            caption: None,
+            honkard_width: None,
+            transform: None,
+        };
+        let gacha = Some("UI_Gacha_AvatarImg_Hutao".to_owned());
+
+        let card = Card::new(&avatar, &characters, &localizations, &assets);
+        assert_eq!(card.art_name(), gacha);
+        let card = Card {
+            settings: Some(&settings),
+            ..card
+        };
+        assert_eq!(card.art_name(), gacha);
+
+        avatar.costume_id = Some(204601);
+        let card = Card {
+            settings: Some(&settings),
+            ..Card::new(&avatar, &characters, &localizations, &assets)
+        };
+        assert_eq!(
+            card.art_name().as_deref(),
+            Some("UI_Costume_HutaoCostumeButterfly")
+        );
+        assert!(card.header(0.0).contains("Lv90/90 · C3"));
+    }
+}
@@ -0,0 +1,445 @@
+//! Damage estimations built on [`super::calc::Stats`] and [`super::api::Talents`]
+//!
+//! The formulas are the ones used by the game. Talent multipliers are read from
+//! [`super::store::excel::ProudSkill::param_list`] at the talent levels decoded by
+//! [`AvatarInfo::talents`], and the reaction base damage from [`Excel::level_multiplier`] at the
+//! attacker level. Conditional buffs are not modelled and have to be added to [`Hit::dmg_bonus`]
+//! or to the [`Attacker::stats`].
+use super::{
+    api::{AvatarInfo, Element, FightProp},
+    calc::Stats,
+    store::{Characters, excel::Excel},
+};
+use std::collections::HashMap;
+
+/// Character dealing the damage
+#[derive(Debug, Clone)]
+pub struct Attacker {
+    pub level: u8,
+    pub stats: Stats,
+    /// Multipliers of every talent at its current level, see
+    /// [`super::store::excel::ProudSkill::param_list`]
+    pub params: HashMap<TalentKind, Vec<f64>>,
+    /// Reaction base damage at [`Self::level`]
+    pub level_multiplier: f64,
+}
+
+impl Attacker {
+    /// Uses the stats computed by the game, `None` when the talents cannot be decoded (see
+    /// [`AvatarInfo::talents`]) or `excel` lacks their level or the attacker level
+    pub fn new(avatar: &AvatarInfo, characters: &Characters, excel: &Excel) -> Option<Self> {
+        let level = avatar.level()?;
+        let talents = avatar.talents(characters)?;
+        let character = characters.get(avatar.avatar_id, avatar.skill_depot_id)?;
+        let params = [
+            (TalentKind::NormalAttack, talents.normal_attack),
+            (TalentKind::Skill, talents.skill),
+            (TalentKind::Burst, talents.burst),
+        ]
+        .into_iter()
+        .map(|(kind, talent)| {
+            let group = *character.proud_map.get(&talent.id)?;
+            let skill = excel.proud_skill_level(group, talent.level())?;
+            Some((kind, skill.param_list.clone()))
+        })
+        .collect::<Option<_>>()?;
+        Some(Attacker {
+            level,
+            stats: Stats::from_avatar(avatar),
+            params,
+            level_multiplier: excel.level_multiplier(level)?,
+        })
+    }
+
+    /// Damage of `hit` against `enemy`, `None` if [`Self::params`] lacks [`Hit::param`] or
+    /// `reaction` cannot be triggered by [`Hit::element`]
+    pub fn damage(&self, hit: &Hit, enemy: &Enemy, reaction: Option<Reaction>) -> Option<Damage> {
+        let multiplier = *self.params.get(&hit.talent)?.get(hit.param)?;
+        let em = self.stats.get(FightProp::ElementMastery);
+        let mut base = multiplier * self.stats.get(hit.scaling) + hit.flat;
+        let amplifying =
+            |multiplier: f64| multiplier * (1.0 + 2.78 * em / (em + 1400.0) + hit.reaction_bonus);
+        let additive = |multiplier: f64| {
+            multiplier
+                * self.level_multiplier
+                * (1.0 + 5.0 * em / (em + 1200.0) + hit.reaction_bonus)
+        };
+        let mut amplifier = 1.0;
+        match (reaction, hit.element) {
+            (None, _) => {}
+            (Some(Reaction::Vaporize), Some(Element::Hydro))
+            | (Some(Reaction::Melt), Some(Element::Pyro)) => amplifier = amplifying(2.0),
+            (Some(Reaction::Vaporize), Some(Element::Pyro))
+            | (Some(Reaction::Melt), Some(Element::Cryo)) => amplifier = amplifying(1.5),
+            (Some(Reaction::Aggravate), Some(Element::Electro)) => base += additive(1.15),
+            (Some(Reaction::Spread), Some(Element::Dendro)) => base += additive(1.25),
+            (Some(_), _) => return None,
+        }
+        let dmg_bonus = match hit.element {
+            Some(element) => self.stats.get(FightProp::damage_bonus(element)),
+            None => self.stats.get(FightProp::PhysicalAddHurt),
+        } + hit.dmg_bonus;
+        let non_crit = base
+            * (1.0 + dmg_bonus)
+            * enemy.defense_multiplier(self.level)
+            * enemy.resistance_multiplier(hit.element)
+            * amplifier;
+        let crit_rate = self.stats.get(FightProp::Critical).clamp(0.0, 1.0);
+        let crit_dmg = self.stats.get(FightProp::CriticalHurt);
+        Some(Damage {
+            non_crit,
+            crit: non_crit * (1.0 + crit_dmg),
+            average: non_crit * (1.0 + crit_rate * crit_dmg),
+        })
+    }
+
+    /// Damage of a transformative reaction triggered by the attacker, which cannot crit and
+    /// ignores defense
+    pub fn transformative(
+        &self,
+        reaction: Transformative,
+        enemy: &Enemy,
+        reaction_bonus: f64,
+    ) -> f64 {
+        let em = self.stats.get(FightProp::ElementMastery);
+        reaction.multiplier()
+            * self.level_multiplier
+            * (1.0 + 16.0 * em / (em + 2000.0) + reaction_bonus)
+            * enemy.resistance_multiplier(reaction.element())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Damage {
+    pub non_crit: f64,
+    pub crit: f64,
+    /// Weighted by the crit rate
+    pub average: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TalentKind {
+    NormalAttack,
+    Skill,
+    Burst,
+}
+
+/// A single damage instance of a talent
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub talent: TalentKind,
+    /// Index of the multiplier in [`Attacker::params`], a value of `1.0` there being 100% of
+    /// [`Self::scaling`]
+    pub param: usize,
+    /// Stat the multiplier applies to, usually [`FightProp::CurAttack`]
+    pub scaling: FightProp,
+    /// `None` for physical damage
+    pub element: Option<Element>,
+    /// Added to the base damage, before bonuses
+    pub flat: f64,
+    /// Added to the DMG bonus of [`Self::element`]
+    pub dmg_bonus: f64,
+    /// Added to the reaction bonus from elemental mastery
+    pub reaction_bonus: f64,
+}
+
+impl Hit {
+    pub fn new(
+        talent: TalentKind,
+        param: usize,
+        scaling: FightProp,
+        element: Option<Element>,
+    ) -> Self {
+        Hit {
+            talent,
+            param,
+            scaling,
+            element,
+            flat: 0.0,
+            dmg_bonus: 0.0,
+            reaction_bonus: 0.0,
+        }
+    }
+}
+
+/// Reactions modifying the damage of the hit triggering them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reaction {
+    Vaporize,
+    Melt,
+    Aggravate,
+    Spread,
+}
+
+/// Reactions dealing damage of their own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transformative {
+    Overloaded,
+    Superconduct,
+    ElectroCharged,
+    /// Of the swirled element
+    Swirl(Element),
+    Shattered,
+    Bloom,
+    Hyperbloom,
+    Burgeon,
+    Burning,
+}
+
+impl Transformative {
+    pub fn multiplier(&self) -> f64 {
+        match self {
+            Transformative::Overloaded => 2.75,
+            Transformative::Superconduct => 1.5,
+            Transformative::ElectroCharged => 2.0,
+            Transformative::Swirl(_) => 0.6,
+            Transformative::Shattered => 3.0,
+            Transformative::Bloom => 2.0,
+            Transformative::Hyperbloom | Transformative::Burgeon => 3.0,
+            Transformative::Burning => 0.25,
+        }
+    }
+
+    /// Element of the damage dealt, `None` for physical
+    pub fn element(&self) -> Option<Element> {
+        match self {
+            Transformative::Overloaded | Transformative::Burning => Some(Element::Pyro),
+            Transformative::Superconduct => Some(Element::Cryo),
+            Transformative::ElectroCharged => Some(Element::Electro),
+            Transformative::Swirl(element) => Some(*element),
+            Transformative::Shattered => None,
+            Transformative::Bloom | Transformative::Hyperbloom | Transformative::Burgeon => {
+                Some(Element::Dendro)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enemy {
+    pub level: u8,
+    /// Resistance used for the elements missing from [`Self::resistances`], 10% for most enemies
+    pub base_resistance: f64,
+    /// Resistance by element, `None` being physical
+    pub resistances: HashMap<Option<Element>, f64>,
+    /// Subtracted from every resistance
+    pub resistance_shred: f64,
+    /// Defense reduction, e.g. from Lisa's A4
+    pub defense_shred: f64,
+    /// Defense ignore, e.g. from Raiden's C2
+    pub defense_ignore: f64,
+}
+
+impl Default for Enemy {
+    fn default() -> Self {
+        Enemy {
+            level: 90,
+            base_resistance: 0.1,
+            resistances: HashMap::new(),
+            resistance_shred: 0.0,
+            defense_shred: 0.0,
+            defense_ignore: 0.0,
+        }
+    }
+}
+
+impl Enemy {
+    pub fn resistance(&self, element: Option<Element>) -> f64 {
+        self.resistances
+            .get(&element)
+            .copied()
+            .unwrap_or(self.base_resistance)
+            - self.resistance_shred
+    }
+
+    pub fn resistance_multiplier(&self, element: Option<Element>) -> f64 {
+        match self.resistance(element) {
+            res if res < 0.0 => 1.0 - res / 2.0,
+            res if res < 0.75 => 1.0 - res,
+            res => 1.0 / (4.0 * res + 1.0),
+        }
+    }
+
+    pub fn defense_multiplier(&self, attacker_level: u8) -> f64 {
+        let attacker = f64::from(attacker_level) + 100.0;
+        let enemy = (f64::from(self.level) + 100.0)
+            * (1.0 - self.defense_shred).max(0.0)
+            * (1.0 - self.defense_ignore).max(0.0);
+        attacker / (attacker + enemy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gi::api::tests::fixture;
+
+    /// Reaction base damage of a level 90 character
+    const LEVEL_MULTIPLIER: f64 = 1446.85;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9 * b.abs().max(1.0), "{a} != {b}");
+    }
+
+    fn attacker(em: f64) -> Attacker {
+        Attacker {
+            level: 90,
+            stats: Stats(HashMap::from([
+                (FightProp::CurAttack, 1000.0),
+                (FightProp::Critical, 0.5),
+                (FightProp::CriticalHurt, 1.0),
+                (FightProp::ElementMastery, em),
+            ])),
+            params: HashMap::from([
+                (TalentKind::NormalAttack, vec![1.0]),
+                (TalentKind::Skill, vec![1.0]),
+                (TalentKind::Burst, vec![1.0]),
+            ]),
+            level_multiplier: LEVEL_MULTIPLIER,
+        }
+    }
+
+    fn hit(element: Element) -> Hit {
+        Hit::new(TalentKind::Skill, 0, FightProp::CurAttack, Some(element))
+    }
+    #[test]
+    fn defense_multiplier() {
+        let enemy = Enemy::default();
+        assert_close(enemy.defense_multiplier(90), 0.5);
+        assert_close(enemy.defense_multiplier(80), 180.0 / 370.0);
+        let enemy = Enemy {
+            level: 100,
+            defense_shred: 0.3,
+            defense_ignore: 0.6,
+            ..Enemy::default()
+        };
+        assert_close(
+            enemy.defense_multiplier(90),
+            190.0 / (190.0 + 200.0 * 0.7 * 0.4),
+        );
+        let enemy = Enemy {
+            defense_shred: 1.5,
+            ..Enemy::default()
+        };
+        assert_close(enemy.defense_multiplier(90), 1.0);
+    }
+
+    #[test]
+    fn resistance_multiplier() {
+        let mut enemy = Enemy::default();
+        assert_close(enemy.resistance_multiplier(Some(Element::Pyro)), 0.9);
+        enemy.resistance_shred = 0.3;
+        assert_close(enemy.resistance_multiplier(Some(Element::Pyro)), 1.1);
+        enemy.resistances.insert(None, 0.7);
+        assert_close(enemy.resistance_multiplier(None), 0.6);
+        enemy.resistance_shred = 0.0;
+        assert_close(enemy.resistance_multiplier(None), 0.3);
+        enemy.resistances.insert(None, 0.75);
+        assert_close(enemy.resistance_multiplier(None), 0.25);
+        enemy.resistances.insert(None, 1.0);
+        assert_close(enemy.resistance_multiplier(None), 0.2);
+    }
+
+    #[test]
+    fn crit() {
+        let damage = attacker(0.0)
+            .damage(&hit(Element::Pyro), &Enemy::default(), None)
+            .unwrap();
+        assert_close(damage.non_crit, 450.0);
+        assert_close(damage.crit, 900.0);
+        assert_close(damage.average, 675.0);
+        let mut hit = hit(Element::Pyro);
+        hit.param = 1;
+        assert!(
+            attacker(0.0)
+                .damage(&hit, &Enemy::default(), None)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn amplifying() {
+        let enemy = Enemy::default();
+        let damage = |em, element, reaction| {
+            attacker(em)
+                .damage(&hit(element), &enemy, Some(reaction))
+                .map(|v| v.non_crit)
+        };
+        assert_eq!(damage(0.0, Element::Hydro, Reaction::Vaporize), Some(900.0));
+        assert_eq!(damage(0.0, Element::Pyro, Reaction::Vaporize), Some(675.0));
+        assert_eq!(damage(0.0, Element::Pyro, Reaction::Melt), Some(900.0));
+        assert_eq!(damage(0.0, Element::Cryo, Reaction::Melt), Some(675.0));
+        assert_close(
+            damage(100.0, Element::Hydro, Reaction::Vaporize).unwrap(),
+            900.0 * (1.0 + 2.78 * 100.0 / 1500.0),
+        );
+        assert_eq!(damage(0.0, Element::Electro, Reaction::Melt), None);
+    }
+
+    #[test]
+    fn additive() {
+        let enemy = Enemy::default();
+        let damage = attacker(0.0)
+            .damage(&hit(Element::Electro), &enemy, Some(Reaction::Aggravate))
+            .unwrap();
+        assert_close(damage.non_crit, (1000.0 + 1.15 * LEVEL_MULTIPLIER) * 0.45);
+        let damage = attacker(200.0)
+            .damage(&hit(Element::Dendro), &enemy, Some(Reaction::Spread))
+            .unwrap();
+        let bonus = 1.25 * LEVEL_MULTIPLIER * (1.0 + 5.0 * 200.0 / 1400.0);
+        assert_close(damage.non_crit, (1000.0 + bonus) * 0.45);
+        assert!(
+            attacker(0.0)
+                .damage(&hit(Element::Pyro), &enemy, Some(Reaction::Spread))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn transformative() {
+        let enemy = Enemy::default();
+        assert_close(
+            attacker(0.0).transformative(Transformative::Overloaded, &enemy, 0.0),
+            2.75 * LEVEL_MULTIPLIER * 0.9,
+        );
+        assert_close(
+            attacker(100.0).transformative(Transformative::Swirl(Element::Hydro), &enemy, 0.6),
+            0.6 * LEVEL_MULTIPLIER * (1.0 + 16.0 * 100.0 / 2100.0 + 0.6) * 0.9,
+        );
+        let enemy = Enemy {
+            resistances: HashMap::from([(None, 0.3)]),
+            ..Enemy::default()
+        };
+        assert_close(
+            attacker(0.0).transformative(Transformative::Shattered, &enemy, 0.0),
+            3.0 * LEVEL_MULTIPLIER * 0.7,
+        );
+    }
+
+    #[test]
+    fn from_avatar() {
+        let avatar = fixture::<AvatarInfo>("avatar.json");
+        let characters = fixture::<Characters>("characters.json");
+        let excel =
+            Excel::from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/excel")).unwrap();
+        let attacker = Attacker::new(&avatar, &characters, &excel).unwrap();
+        assert_eq!(attacker.level, 90);
+        assert_eq!(attacker.level_multiplier, LEVEL_MULTIPLIER);
+        // skill level 10 boosted to 13 by a constellation
+        assert_eq!(attacker.params[&TalentKind::Skill], [0.0721, 1.5232]);
+        assert_eq!(attacker.params[&TalentKind::NormalAttack][0], 0.8363);
+        assert_eq!(attacker.params[&TalentKind::Burst][0], 6.0573);
+        let hit = Hit::new(
+            TalentKind::Skill,
+            1,
+            FightProp::CurAttack,
+            Some(Element::Pyro),
+        );
+        let damage = attacker.damage(&hit, &Enemy::default(), None).unwrap();
+        assert_close(
+            damage.non_crit,
+            attacker.stats.atk() * 1.5232 * (1.0 + 0.616) * 0.5 * 0.9,
+        );
+        assert!(Attacker::new(&avatar, &characters, &Excel::default()).is_none());
+    }
+}
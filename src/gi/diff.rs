@@ -0,0 +1,361 @@
+use super::{
+    api::{AvatarInfo, EquipReliquary, EquipType, EquipWeapon, FightProp, ItemId, Talents},
+    store::Characters,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Value of a field before and after a change
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn new(before: T, after: T) -> Option<Self> {
+        (before != after).then_some(Change { before, after })
+    }
+}
+
+/// Value of a stat before and after a change, 0 when the stat is missing
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatChange<K> {
+    pub stat: K,
+    pub before: f64,
+    pub after: f64,
+}
+
+impl<K> StatChange<K> {
+    pub fn delta(&self) -> f64 {
+        self.after - self.before
+    }
+}
+
+/// Differences between two snapshots of the same character, see [`diff`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AvatarDiff {
+    pub level: Option<Change<Option<u8>>>,
+    pub ascension: Option<Change<u8>>,
+    pub constellation: Option<Change<u8>>,
+    /// `None` when unchanged or when [`AvatarInfo::talents`] cannot resolve either snapshot
+    pub talents: Option<Change<Talents>>,
+    pub weapon: Option<WeaponChange>,
+    /// Artifact changes, sorted by slot
+    pub artifacts: Vec<ArtifactChange>,
+    /// Final stat changes, sorted by fight prop, ignoring the props unknown to [`FightProp`]
+    pub stats: Vec<StatChange<FightProp>>,
+}
+
+impl AvatarDiff {
+    pub fn is_empty(&self) -> bool {
+        self == &AvatarDiff::default()
+    }
+}
+
+/// Changes of the weapon, `None` standing for no weapon equipped
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WeaponChange {
+    /// Set when the weapon was equipped, removed or swapped for another one
+    pub item_id: Option<Change<Option<ItemId>>>,
+    pub level: Option<Change<Option<u8>>>,
+    pub ascension: Option<Change<Option<u8>>>,
+    pub refinement: Option<Change<Option<u8>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtifactChange {
+    pub slot: EquipType,
+    /// Set when the piece was equipped, removed or swapped for another kind of piece, `None`
+    /// standing for an empty slot
+    pub item_id: Option<Change<Option<ItemId>>>,
+    pub set_id: Option<Change<Option<u64>>>,
+    pub level: Option<Change<Option<u8>>>,
+    pub main_stat: Option<Change<Option<(String, f64)>>>,
+    /// Substat changes, sorted by stat
+    pub substats: Vec<StatChange<String>>,
+}
+
+/// Compares two snapshots of the same character, e.g. two [`AvatarInfo`]s returned by
+/// [`super::get_player`] at different times, or a live one and the
+/// [`super::api::profile::hoyo::build::Build::avatar_data`] of a saved build
+///
+/// `characters` is used to tell the normal attack, elemental skill and elemental burst apart.
+pub fn diff(before: &AvatarInfo, after: &AvatarInfo, characters: &Characters) -> AvatarDiff {
+    let (mut artifacts_before, mut artifacts_after) = (artifacts(before), artifacts(after));
+    let slots = artifacts_before
+        .keys()
+        .chain(artifacts_after.keys())
+        .copied()
+        .collect::<BTreeSet<_>>();
+    AvatarDiff {
+        level: Change::new(before.level(), after.level()),
+        ascension: Change::new(before.ascension(), after.ascension()),
+        constellation: Change::new(before.constellation(), after.constellation()),
+        talents: before
+            .talents(characters)
+            .zip(after.talents(characters))
+            .and_then(|(before, after)| Change::new(before, after)),
+        weapon: diff_weapon(before.weapon(), after.weapon()),
+        artifacts: slots
+            .into_iter()
+            .filter_map(|slot| {
+                diff_artifact(
+                    slot,
+                    artifacts_before.remove(&slot),
+                    artifacts_after.remove(&slot),
+                )
+            })
+            .collect(),
+        stats: diff_stats(fight_props(before), fight_props(after)),
+    }
+}
+
+fn artifacts(avatar: &AvatarInfo) -> BTreeMap<EquipType, &EquipReliquary> {
+    avatar.artifacts().map(|v| (v.flat.equip_type, v)).collect()
+}
+
+fn fight_props(avatar: &AvatarInfo) -> impl Iterator<Item = (FightProp, f64)> + '_ {
+    avatar
+        .fight_prop_map
+        .iter()
+        .filter_map(|(&id, &v)| Some((FightProp::from_id(id)?, v)))
+}
+
+fn diff_weapon(before: Option<&EquipWeapon>, after: Option<&EquipWeapon>) -> Option<WeaponChange> {
+    let change = WeaponChange {
+        item_id: Change::new(before.map(|v| v.item_id), after.map(|v| v.item_id)),
+        level: Change::new(
+            before.map(|v| v.weapon.level),
+            after.map(|v| v.weapon.level),
+        ),
+        ascension: Change::new(before.map(|v| v.ascension()), after.map(|v| v.ascension())),
+        refinement: Change::new(
+            before.map(|v| v.refinement()),
+            after.map(|v| v.refinement()),
+        ),
+    };
+    (change != WeaponChange::default()).then_some(change)
+}
+
+fn diff_artifact(
+    slot: EquipType,
+    before: Option<&EquipReliquary>,
+    after: Option<&EquipReliquary>,
+) -> Option<ArtifactChange> {
+    let main_stat = |v: Option<&EquipReliquary>| {
+        v.map(|v| {
+            let stat = &v.flat.reliquary_mainstat;
+            (stat.main_prop_id.clone(), stat.stat_value)
+        })
+    };
+    let substats = |v: Option<&EquipReliquary>| {
+        v.and_then(|v| v.flat.reliquary_substats.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|v| (v.append_prop_id.clone(), v.stat_value))
+            .collect::<Vec<_>>()
+    };
+    let change = ArtifactChange {
+        slot,
+        item_id: Change::new(before.map(|v| v.item_id), after.map(|v| v.item_id)),
+        set_id: Change::new(
+            before.and_then(|v| v.flat.set_id),
+            after.and_then(|v| v.flat.set_id),
+        ),
+        level: Change::new(
+            before.map(|v| v.reliquary.level),
+            after.map(|v| v.reliquary.level),
+        ),
+        main_stat: Change::new(main_stat(before), main_stat(after)),
+        substats: diff_stats(substats(before), substats(after)),
+    };
+    (change.item_id.is_some()
+        || change.set_id.is_some()
+        || change.level.is_some()
+        || change.main_stat.is_some()
+        || !change.substats.is_empty())
+    .then_some(change)
+}
+
+fn diff_stats<K: Ord + Clone>(
+    before: impl IntoIterator<Item = (K, f64)>,
+    after: impl IntoIterator<Item = (K, f64)>,
+) -> Vec<StatChange<K>> {
+    let mut stats = BTreeMap::<K, (f64, f64)>::new();
+    for (k, v) in before {
+        stats.entry(k).or_default().0 += v;
+    }
+    for (k, v) in after {
+        stats.entry(k).or_default().1 += v;
+    }
+    stats
+        .into_iter()
+        .filter(|(_, (before, after))| {
+            (after - before).abs() > f64::EPSILON * before.abs().max(1.0)
+        })
+        .map(|(stat, (before, after))| StatChange {
+            stat,
+            before,
+            after,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gi::api::{Equip, Prop, tests::fixture};
+    use std::collections::HashMap;
+
+    fn avatar() -> (AvatarInfo, Characters) {
+        (fixture("avatar.json"), fixture("characters.json"))
+    }
+
+    fn weapon(avatar: &mut AvatarInfo) -> &mut EquipWeapon {
+        avatar
+            .equip_list
+            .iter_mut()
+            .find_map(|v| match v {
+                Equip::Weapon(v) => Some(v),
+                Equip::Reliquary(_) => None,
+            })
+            .unwrap()
+    }
+
+    fn artifact(avatar: &mut AvatarInfo, slot: EquipType) -> &mut EquipReliquary {
+        avatar
+            .equip_list
+            .iter_mut()
+            .find_map(|v| match v {
+                Equip::Reliquary(v) if v.flat.equip_type == slot => Some(v),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn unchanged() {
+        let (avatar, characters) = avatar();
+        assert!(diff(&avatar, &avatar, &characters).is_empty());
+    }
+
+    #[test]
+    fn level_and_talents() {
+        let (before, characters) = avatar();
+        let mut after = before.clone();
+        after.prop_map.get_mut(&Prop::Level).unwrap().val = Some("80".to_owned());
+        after.skill_level_map.insert(10461, 10);
+        let diff = diff(&before, &after, &characters);
+        assert_eq!(
+            diff.level,
+            Some(Change {
+                before: Some(90),
+                after: Some(80)
+            })
+        );
+        let talents = diff.talents.unwrap();
+        assert_eq!(
+            (
+                talents.before.normal_attack.base,
+                talents.after.normal_attack.base
+            ),
+            (9, 10)
+        );
+        assert_eq!(talents.before.skill, talents.after.skill);
+        assert!(diff.ascension.is_none() && diff.weapon.is_none() && diff.artifacts.is_empty());
+    }
+
+    #[test]
+    fn weapon_changes() {
+        let (before, characters) = avatar();
+        let mut after = before.clone();
+        weapon(&mut after).item_id = 13505;
+        weapon(&mut after).weapon.affix_map = Some(HashMap::from([(113505, 2)]));
+        let change = diff(&before, &after, &characters).weapon.unwrap();
+        assert_eq!(
+            change.item_id,
+            Some(Change {
+                before: Some(13501),
+                after: Some(13505)
+            })
+        );
+        assert_eq!(
+            change.refinement,
+            Some(Change {
+                before: Some(1),
+                after: Some(3)
+            })
+        );
+        assert!(change.level.is_none() && change.ascension.is_none());
+
+        after
+            .equip_list
+            .retain(|v| matches!(v, Equip::Reliquary(_)));
+        let change = diff(&before, &after, &characters).weapon.unwrap();
+        assert_eq!(
+            change.level,
+            Some(Change {
+                before: Some(90),
+                after: None
+            })
+        );
+        let change = diff(&after, &before, &characters).weapon.unwrap();
+        assert_eq!(
+            change.item_id,
+            Some(Change {
+                before: None,
+                after: Some(13501)
+            })
+        );
+    }
+
+    #[test]
+    fn artifact_changes() {
+        let (before, characters) = avatar();
+        let mut after = before.clone();
+        let goblet = artifact(&mut after, EquipType::EquipRing);
+        goblet.item_id = 77541;
+        goblet.flat.set_id = Some(15031);
+        let flower = artifact(&mut after, EquipType::EquipBracer);
+        flower.flat.reliquary_substats.as_mut().unwrap()[0].stat_value = 10.5;
+        let diff = diff(&before, &after, &characters);
+        assert_eq!(
+            diff.artifacts.iter().map(|v| v.slot).collect::<Vec<_>>(),
+            [EquipType::EquipBracer, EquipType::EquipRing]
+        );
+        let [flower, goblet] = diff.artifacts.as_slice() else {
+            unreachable!()
+        };
+        assert!(flower.item_id.is_none() && flower.main_stat.is_none());
+        assert_eq!(
+            flower.substats,
+            [StatChange {
+                stat: "FIGHT_PROP_CRITICAL".to_owned(),
+                before: 7.0,
+                after: 10.5
+            }]
+        );
+        assert!((flower.substats[0].delta() - 3.5).abs() < 1e-9);
+        assert_eq!(
+            goblet.set_id,
+            Some(Change {
+                before: Some(15006),
+                after: Some(15031)
+            })
+        );
+        assert!(goblet.substats.is_empty());
+    }
+
+    #[test]
+    fn stat_changes() {
+        let (before, characters) = avatar();
+        let mut after = before.clone();
+        after.fight_prop_map.insert(FightProp::Critical.id(), 0.676);
+        // unknown to `FightProp`
+        after.fight_prop_map.insert(3045, 1.0);
+        let diff = diff(&before, &after, &characters);
+        assert_eq!(diff.stats.len(), 1);
+        assert_eq!(diff.stats[0].stat, FightProp::Critical);
+        assert!((diff.stats[0].delta() - 0.035).abs() < 1e-9);
+    }
+}
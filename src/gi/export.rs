@@ -0,0 +1,4 @@
+//! Conversions of showcases to formats understood by other tools
+pub mod good;
+#[cfg(feature = "tabular")]
+pub mod tabular;
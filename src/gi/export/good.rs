@@ -0,0 +1,808 @@
+//! [GOOD](https://frzyc.github.io/genshin-optimizer/#/doc), the Genshin Open Object Description
+//! format used by Genshin Optimizer and similar tools
+//!
+//! GOOD identifies characters, weapons and artifact sets by their English name in PascalCase, so
+//! exporting and importing require the [`Characters`] and [`Localizations`] stores.
+use crate::gi::{
+    api::{
+        AvatarId, AvatarInfo, Equip, EquipReliquary, EquipType, EquipWeapon, FightProp, ItemId,
+        SkillId, TextMapHash, profile::hoyo::builds::Builds,
+    },
+    store::{Characters, Localizations},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Good {
+    /// Always `GOOD`
+    pub format: String,
+    pub version: u8,
+    /// Tool that produced the document
+    pub source: String,
+    #[serde(default)]
+    pub characters: Vec<Character>,
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+    #[serde(default)]
+    pub weapons: Vec<Weapon>,
+}
+
+impl Default for Good {
+    fn default() -> Self {
+        Good {
+            format: "GOOD".to_owned(),
+            version: 2,
+            source: "enka-rs".to_owned(),
+            characters: Vec::new(),
+            artifacts: Vec::new(),
+            weapons: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Character {
+    pub key: String,
+    pub level: u8,
+    pub constellation: u8,
+    pub ascension: u8,
+    pub talent: Talent,
+}
+
+/// Talent levels without the bonus from constellations
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Talent {
+    pub auto: u8,
+    pub skill: u8,
+    pub burst: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Weapon {
+    pub key: String,
+    pub level: u8,
+    pub ascension: u8,
+    pub refinement: u8,
+    /// Key of the character equipping it, empty if none
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub lock: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+    pub set_key: String,
+    pub slot_key: SlotKey,
+    /// 0 to 20
+    pub level: u8,
+    pub rarity: u8,
+    pub main_stat_key: String,
+    /// Key of the character equipping it, empty if none
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub lock: bool,
+    #[serde(default)]
+    pub substats: Vec<Substat>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SlotKey {
+    Flower,
+    Plume,
+    Sands,
+    Goblet,
+    Circlet,
+}
+
+impl From<EquipType> for SlotKey {
+    fn from(equip_type: EquipType) -> Self {
+        match equip_type {
+            EquipType::EquipBracer => SlotKey::Flower,
+            EquipType::EquipNecklace => SlotKey::Plume,
+            EquipType::EquipShoes => SlotKey::Sands,
+            EquipType::EquipRing => SlotKey::Goblet,
+            EquipType::EquipDress => SlotKey::Circlet,
+        }
+    }
+}
+
+impl From<SlotKey> for EquipType {
+    fn from(slot_key: SlotKey) -> Self {
+        match slot_key {
+            SlotKey::Flower => EquipType::EquipBracer,
+            SlotKey::Plume => EquipType::EquipNecklace,
+            SlotKey::Sands => EquipType::EquipShoes,
+            SlotKey::Goblet => EquipType::EquipRing,
+            SlotKey::Circlet => EquipType::EquipDress,
+        }
+    }
+}
+
+/// Value as displayed in game, percentages not being divided by 100
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Substat {
+    pub key: String,
+    pub value: f64,
+}
+
+/// Names GOOD gives to the stats that can roll on artifacts
+const STAT_KEYS: [(FightProp, &str); 19] = [
+    (FightProp::Hp, "hp"),
+    (FightProp::HpPercent, "hp_"),
+    (FightProp::Attack, "atk"),
+    (FightProp::AttackPercent, "atk_"),
+    (FightProp::Defense, "def"),
+    (FightProp::DefensePercent, "def_"),
+    (FightProp::ElementMastery, "eleMas"),
+    (FightProp::ChargeEfficiency, "enerRech_"),
+    (FightProp::HealAdd, "heal_"),
+    (FightProp::Critical, "critRate_"),
+    (FightProp::CriticalHurt, "critDMG_"),
+    (FightProp::PhysicalAddHurt, "physical_dmg_"),
+    (FightProp::FireAddHurt, "pyro_dmg_"),
+    (FightProp::ElecAddHurt, "electro_dmg_"),
+    (FightProp::WaterAddHurt, "hydro_dmg_"),
+    (FightProp::GrassAddHurt, "dendro_dmg_"),
+    (FightProp::WindAddHurt, "anemo_dmg_"),
+    (FightProp::RockAddHurt, "geo_dmg_"),
+    (FightProp::IceAddHurt, "cryo_dmg_"),
+];
+
+pub fn stat_key(prop: FightProp) -> Option<&'static str> {
+    STAT_KEYS.iter().find(|v| v.0 == prop).map(|v| v.1)
+}
+
+pub fn stat_prop(key: &str) -> Option<FightProp> {
+    STAT_KEYS.iter().find(|v| v.1 == key).map(|v| v.0)
+}
+
+/// PascalCase version of an English name without punctuation, e.g. `Wolf's Gravestone` becomes
+/// `WolfsGravestone`
+pub fn key(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .flat_map(|word| {
+            let mut chars = word.chars().filter(|c| c.is_alphanumeric());
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect()
+}
+
+fn name(localizations: &Localizations, hash: impl std::fmt::Display) -> Option<String> {
+    localizations.get("en", hash).map(key)
+}
+
+fn character_key(
+    avatar: &AvatarInfo,
+    characters: &Characters,
+    localizations: &Localizations,
+) -> Option<String> {
+    match avatar.traveler_element() {
+        Some(element) => Some(format!("Traveler{element}")),
+        None => name(
+            localizations,
+            characters
+                .get(avatar.avatar_id, avatar.skill_depot_id)?
+                .name_text_map_hash?,
+        ),
+    }
+}
+
+/// GOOD location of the character `key`, travelers of every element sharing `Traveler`
+fn location_key(key: &str) -> &str {
+    if key.starts_with("Traveler") {
+        "Traveler"
+    } else {
+        key
+    }
+}
+
+/// Data that could not be mapped between GOOD and enka.network and was left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unmapped {
+    /// Missing from the [`Characters`] or [`Localizations`] stores, its equipment is exported
+    /// without a location
+    Character(AvatarId),
+    /// Name missing from the [`Localizations`] store
+    Weapon(ItemId),
+    /// Name missing from the [`Localizations`] store
+    ArtifactSet(TextMapHash),
+    /// Stat without a GOOD name, e.g. on a weapon or an unknown prop
+    Stat(String),
+    /// GOOD key unknown to the [`Importer`], its item is imported without an ID when it is a weapon
+    /// or an artifact set
+    Key(String),
+}
+
+/// Accumulates characters into a [`Good`] document
+#[derive(Debug, Clone)]
+pub struct Exporter<'a> {
+    characters: &'a Characters,
+    localizations: &'a Localizations,
+    good: Good,
+    unmapped: Vec<Unmapped>,
+    /// Characters already exported, by avatar and skill depot IDs
+    exported: HashSet<(AvatarId, SkillId)>,
+}
+
+impl<'a> Exporter<'a> {
+    pub fn new(characters: &'a Characters, localizations: &'a Localizations) -> Self {
+        Exporter {
+            characters,
+            localizations,
+            good: Good::default(),
+            unmapped: Vec::new(),
+            exported: HashSet::new(),
+        }
+    }
+
+    /// Adds every character, e.g. from [`crate::gi::get_player`], characters already added being
+    /// skipped along with their equipment
+    pub fn avatars<'b>(mut self, avatars: impl IntoIterator<Item = &'b AvatarInfo>) -> Self {
+        for avatar in avatars {
+            self.push(avatar);
+        }
+        self
+    }
+
+    /// Adds the live build of every character, or the first saved one when there is none
+    pub fn builds(self, builds: &Builds) -> Self {
+        let avatars = builds
+            .iter_by_avatar()
+            .filter_map(|(id, mut saved)| builds.live(id).or_else(|| saved.next()))
+            .map(|v| &v.avatar_data)
+            .collect::<Vec<_>>();
+        self.avatars(avatars)
+    }
+
+    /// The document along with everything that could not be exported
+    pub fn finish(self) -> (Good, Vec<Unmapped>) {
+        (self.good, self.unmapped)
+    }
+
+    fn name(&self, hash: impl std::fmt::Display) -> Option<String> {
+        name(self.localizations, hash)
+    }
+
+    fn push(&mut self, avatar: &AvatarInfo) {
+        if !self
+            .exported
+            .insert((avatar.avatar_id, avatar.skill_depot_id))
+        {
+            return;
+        }
+        let key = self.character(avatar);
+        if key.is_none() {
+            self.unmapped.push(Unmapped::Character(avatar.avatar_id));
+        }
+        let location = key.as_deref().map_or("", location_key).to_owned();
+        for equip in &avatar.equip_list {
+            match equip {
+                Equip::Weapon(v) => self.weapon(v, &location),
+                Equip::Reliquary(v) => self.artifact(v, &location),
+            }
+        }
+    }
+
+    fn character(&mut self, avatar: &AvatarInfo) -> Option<String> {
+        let key = character_key(avatar, self.characters, self.localizations)?;
+        let talents = avatar.talents(self.characters)?;
+        if !self.good.characters.iter().any(|v| v.key == key) {
+            self.good.characters.push(Character {
+                key: key.clone(),
+                level: avatar.level()?,
+                constellation: avatar.constellation(),
+                ascension: avatar.ascension(),
+                talent: Talent {
+                    auto: talents.normal_attack.base,
+                    skill: talents.skill.base,
+                    burst: talents.burst.base,
+                },
+            });
+        }
+        Some(key)
+    }
+
+    fn weapon(&mut self, weapon: &EquipWeapon, location: &str) {
+        let Some(key) = self.name(&weapon.flat.name_text_map_hash) else {
+            self.unmapped.push(Unmapped::Weapon(weapon.item_id));
+            return;
+        };
+        self.good.weapons.push(Weapon {
+            key,
+            level: weapon.weapon.level,
+            ascension: weapon.ascension(),
+            refinement: weapon.refinement(),
+            location: location.to_owned(),
+            lock: false,
+        });
+    }
+
+    fn artifact(&mut self, artifact: &EquipReliquary, location: &str) {
+        let flat = &artifact.flat;
+        let Some(set_key) = self.name(&flat.set_name_text_map_hash) else {
+            self.unmapped
+                .push(Unmapped::ArtifactSet(flat.set_name_text_map_hash.clone()));
+            return;
+        };
+        let Some(main_stat_key) = flat.reliquary_mainstat.prop().and_then(stat_key) else {
+            self.unmapped
+                .push(Unmapped::Stat(flat.reliquary_mainstat.main_prop_id.clone()));
+            return;
+        };
+        let mut substats = Vec::new();
+        for substat in flat.reliquary_substats.iter().flatten() {
+            match substat.prop().and_then(stat_key) {
+                Some(key) => substats.push(Substat {
+                    key: key.to_owned(),
+                    value: substat.stat_value,
+                }),
+                None => self
+                    .unmapped
+                    .push(Unmapped::Stat(substat.append_prop_id.clone())),
+            }
+        }
+        self.good.artifacts.push(Artifact {
+            set_key,
+            slot_key: flat.equip_type.into(),
+            // enka.network counts from 1
+            level: artifact.reliquary.level.saturating_sub(1),
+            rarity: flat.rank_level,
+            main_stat_key: main_stat_key.to_owned(),
+            location: location.to_owned(),
+            lock: false,
+            substats,
+        });
+    }
+}
+
+/// Character with its equipment, in a form shared by GOOD documents and [`AvatarInfo`] so that an
+/// optimizer plan can be compared with a live showcase
+///
+/// Weapons and sets are identified by their GOOD key since GOOD does not carry item IDs, along
+/// with the IDs resolved by the [`Importer`] when importing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loadout {
+    pub key: String,
+    pub level: u8,
+    pub ascension: u8,
+    pub constellation: u8,
+    pub talent: Talent,
+    pub weapon: Option<LoadoutWeapon>,
+    pub artifacts: BTreeMap<EquipType, LoadoutArtifact>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadoutWeapon {
+    pub key: String,
+    /// `None` when imported from GOOD and unknown to the [`Importer`]
+    pub item_id: Option<ItemId>,
+    pub level: u8,
+    pub ascension: u8,
+    pub refinement: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadoutArtifact {
+    pub set_key: String,
+    /// `None` when imported from GOOD and unknown to the [`Importer`]
+    pub set_id: Option<u64>,
+    /// 0 to 20
+    pub level: u8,
+    pub rarity: u8,
+    pub main_stat: FightProp,
+    /// Values as displayed in game, see [`Substat`]
+    pub substats: Vec<(FightProp, f64)>,
+}
+
+impl Loadout {
+    /// `None` if the character is missing from the stores, equipment that cannot be named being
+    /// left out
+    pub fn from_avatar(
+        avatar: &AvatarInfo,
+        characters: &Characters,
+        localizations: &Localizations,
+    ) -> Option<Self> {
+        let talents = avatar.talents(characters)?;
+        let mut loadout = Loadout {
+            key: character_key(avatar, characters, localizations)?,
+            level: avatar.level()?,
+            ascension: avatar.ascension(),
+            constellation: avatar.constellation(),
+            talent: Talent {
+                auto: talents.normal_attack.base,
+                skill: talents.skill.base,
+                burst: talents.burst.base,
+            },
+            weapon: None,
+            artifacts: BTreeMap::new(),
+        };
+        for equip in &avatar.equip_list {
+            match equip {
+                Equip::Weapon(v) => {
+                    loadout.weapon =
+                        name(localizations, &v.flat.name_text_map_hash).map(|key| LoadoutWeapon {
+                            key,
+                            item_id: Some(v.item_id),
+                            level: v.weapon.level,
+                            ascension: v.ascension(),
+                            refinement: v.refinement(),
+                        })
+                }
+                Equip::Reliquary(v) => {
+                    let flat = &v.flat;
+                    let (Some(set_key), Some(main_stat)) = (
+                        name(localizations, &flat.set_name_text_map_hash),
+                        flat.reliquary_mainstat.prop(),
+                    ) else {
+                        continue;
+                    };
+                    loadout.artifacts.insert(
+                        flat.equip_type,
+                        LoadoutArtifact {
+                            set_key,
+                            set_id: flat.set_id,
+                            level: v.reliquary.level.saturating_sub(1),
+                            rarity: flat.rank_level,
+                            main_stat,
+                            substats: flat
+                                .reliquary_substats
+                                .iter()
+                                .flatten()
+                                .filter_map(|v| Some((v.prop()?, v.stat_value)))
+                                .collect(),
+                        },
+                    );
+                }
+            }
+        }
+        Some(loadout)
+    }
+}
+
+/// Maps GOOD keys back to enka.network identifiers
+///
+/// Characters are mapped from the [`Characters`] store, weapons and artifact sets from the
+/// showcases given to [`Importer::avatars`] and [`Importer::builds`]. When several IDs share a
+/// key, the lowest one wins.
+#[derive(Debug, Clone)]
+pub struct Importer<'a> {
+    localizations: &'a Localizations,
+    avatar_ids: HashMap<String, (AvatarId, Option<TextMapHash>)>,
+    weapons: HashMap<String, (ItemId, TextMapHash)>,
+    sets: HashMap<String, (u64, TextMapHash)>,
+}
+
+impl<'a> Importer<'a> {
+    pub fn new(characters: &Characters, localizations: &'a Localizations) -> Self {
+        let mut avatar_ids = HashMap::new();
+        for (id, character) in &characters.0 {
+            let (avatar_id, skill_depot_id) = match id.split_once('-') {
+                Some((avatar_id, skill_depot_id)) => (avatar_id, skill_depot_id.parse().ok()),
+                None => (id.as_str(), None),
+            };
+            let Ok(avatar_id) = avatar_id.parse::<AvatarId>() else {
+                continue;
+            };
+            let hash = character.name_text_map_hash.map(TextMapHash::U64);
+            let key = match skill_depot_id.and_then(|v| avatar_id.traveler_element(v)) {
+                Some(element) => format!("Traveler{element}"),
+                None => match hash.as_ref().and_then(|v| name(localizations, v)) {
+                    Some(key) => key,
+                    None => continue,
+                },
+            };
+            // travelers are listed for both genders, GOOD does not tell them apart
+            insert_lowest(&mut avatar_ids, key, avatar_id, hash);
+        }
+        Importer {
+            localizations,
+            avatar_ids,
+            weapons: HashMap::new(),
+            sets: HashMap::new(),
+        }
+    }
+
+    /// Learns the IDs of the weapons and artifact sets equipped by every character
+    pub fn avatars<'b>(mut self, avatars: impl IntoIterator<Item = &'b AvatarInfo>) -> Self {
+        for avatar in avatars {
+            if let Some(weapon) = avatar.weapon() {
+                let hash = &weapon.flat.name_text_map_hash;
+                if let Some(key) = name(self.localizations, hash) {
+                    insert_lowest(&mut self.weapons, key, weapon.item_id, hash.clone());
+                }
+            }
+            for artifact in avatar.artifacts() {
+                let flat = &artifact.flat;
+                if let (Some(set_id), Some(key)) = (
+                    flat.set_id,
+                    name(self.localizations, &flat.set_name_text_map_hash),
+                ) {
+                    insert_lowest(
+                        &mut self.sets,
+                        key,
+                        set_id,
+                        flat.set_name_text_map_hash.clone(),
+                    );
+                }
+            }
+        }
+        self
+    }
+
+    /// Learns the IDs of the weapons and artifact sets of every saved build
+    pub fn builds(self, builds: &Builds) -> Self {
+        let avatars = builds
+            .values()
+            .flatten()
+            .map(|v| &v.avatar_data)
+            .collect::<Vec<_>>();
+        self.avatars(avatars)
+    }
+
+    /// Character with the given key, Aether for travelers
+    pub fn avatar_id(&self, key: &str) -> Option<AvatarId> {
+        self.avatar_ids.get(key).map(|v| v.0)
+    }
+
+    pub fn weapon_id(&self, key: &str) -> Option<ItemId> {
+        self.weapons.get(key).map(|v| v.0)
+    }
+
+    pub fn set_id(&self, key: &str) -> Option<u64> {
+        self.sets.get(key).map(|v| v.0)
+    }
+
+    /// Text map hash of the English name of a character, weapon or artifact set
+    pub fn text_map_hash(&self, key: &str) -> Option<&TextMapHash> {
+        self.avatar_ids
+            .get(key)
+            .and_then(|v| v.1.as_ref())
+            .or_else(|| self.weapons.get(key).map(|v| &v.1))
+            .or_else(|| self.sets.get(key).map(|v| &v.1))
+    }
+
+    /// Every character of `good` along with the weapon and artifacts located on it
+    pub fn import(&self, good: &Good) -> (Vec<Loadout>, Vec<Unmapped>) {
+        let mut unmapped = Vec::new();
+        let mut loadouts = good
+            .characters
+            .iter()
+            .filter(|v| {
+                let known = self.avatar_ids.contains_key(&v.key);
+                if !known {
+                    unmapped.push(Unmapped::Key(v.key.clone()));
+                }
+                known
+            })
+            .map(|v| {
+                (
+                    v.key.as_str(),
+                    Loadout {
+                        key: v.key.clone(),
+                        level: v.level,
+                        ascension: v.ascension,
+                        constellation: v.constellation,
+                        talent: v.talent,
+                        weapon: None,
+                        artifacts: BTreeMap::new(),
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        for weapon in &good.weapons {
+            let keys = located(&loadouts, &weapon.location);
+            if keys.is_empty() {
+                continue;
+            }
+            let item_id = self.weapon_id(&weapon.key);
+            if item_id.is_none() {
+                push_once(&mut unmapped, Unmapped::Key(weapon.key.clone()));
+            }
+            for key in keys {
+                loadouts.get_mut(key).unwrap().weapon = Some(LoadoutWeapon {
+                    key: weapon.key.clone(),
+                    item_id,
+                    level: weapon.level,
+                    ascension: weapon.ascension,
+                    refinement: weapon.refinement,
+                });
+            }
+        }
+        for artifact in &good.artifacts {
+            let keys = located(&loadouts, &artifact.location);
+            if keys.is_empty() {
+                continue;
+            }
+            let Some(main_stat) = stat_prop(&artifact.main_stat_key) else {
+                unmapped.push(Unmapped::Stat(artifact.main_stat_key.clone()));
+                continue;
+            };
+            let set_id = self.set_id(&artifact.set_key);
+            if set_id.is_none() {
+                push_once(&mut unmapped, Unmapped::Key(artifact.set_key.clone()));
+            }
+            let mut substats = Vec::new();
+            for substat in &artifact.substats {
+                match stat_prop(&substat.key) {
+                    Some(prop) => substats.push((prop, substat.value)),
+                    None => unmapped.push(Unmapped::Stat(substat.key.clone())),
+                }
+            }
+            let loadout_artifact = LoadoutArtifact {
+                set_key: artifact.set_key.clone(),
+                set_id,
+                level: artifact.level,
+                rarity: artifact.rarity,
+                main_stat,
+                substats,
+            };
+            for key in keys {
+                loadouts
+                    .get_mut(key)
+                    .unwrap()
+                    .artifacts
+                    .insert(artifact.slot_key.into(), loadout_artifact.clone());
+            }
+        }
+        let mut loadouts = loadouts.into_values().collect::<Vec<_>>();
+        loadouts.sort_by(|a, b| a.key.cmp(&b.key));
+        (loadouts, unmapped)
+    }
+}
+
+/// Keys of the loadouts wearing the equipment located on `location`, every traveler character
+/// sharing the one located on `Traveler`
+fn located<'k>(loadouts: &HashMap<&'k str, Loadout>, location: &str) -> Vec<&'k str> {
+    let mut keys = loadouts
+        .keys()
+        .copied()
+        .filter(|v| location_key(v) == location)
+        .collect::<Vec<_>>();
+    keys.sort_unstable();
+    keys
+}
+
+/// Reports `item` unless it already was, e.g. a key shared by several unknown items
+fn push_once(unmapped: &mut Vec<Unmapped>, item: Unmapped) {
+    if !unmapped.contains(&item) {
+        unmapped.push(item);
+    }
+}
+
+/// Inserts `id` unless `map` already holds a lower one for `key`, so that the result does not
+/// depend on iteration order
+fn insert_lowest<I: Ord, H>(map: &mut HashMap<String, (I, H)>, key: String, id: I, hash: H) {
+    match map.get(&key) {
+        Some((current, _)) if *current <= id => {}
+        _ => {
+            map.insert(key, (id, hash));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gi::api::tests::fixture;
+
+    #[test]
+    fn export_once() {
+        let avatar = fixture::<AvatarInfo>("avatar.json");
+        let characters = fixture::<Characters>("characters.json");
+        let localizations = fixture::<Localizations>("loc.json");
+        let (good, unmapped) = Exporter::new(&characters, &localizations)
+            .avatars([&avatar])
+            .avatars([&avatar])
+            .finish();
+        assert_eq!(unmapped, []);
+        assert_eq!(good.characters.len(), 1);
+        assert_eq!(good.characters[0].key, "HuTao");
+        assert_eq!(good.characters[0].talent.skill, 10);
+        assert_eq!(good.weapons.len(), 1);
+        assert_eq!(good.weapons[0].key, "StaffOfHoma");
+        assert_eq!(good.artifacts.len(), 5);
+        assert!(good.artifacts.iter().all(|v| v.location == "HuTao"));
+        assert_eq!(good.artifacts[0].level, 20);
+    }
+
+    /// Hu Tao's showcase worn by the Anemo traveler
+    fn traveler() -> (AvatarInfo, Characters) {
+        let mut avatar = fixture::<AvatarInfo>("avatar.json");
+        avatar.avatar_id = 10000005.into();
+        avatar.skill_depot_id = 504;
+        let mut characters = fixture::<Characters>("characters.json");
+        let character = characters.0["10000046"].clone();
+        characters.0.insert("10000005-504".to_owned(), character);
+        (avatar, characters)
+    }
+
+    #[test]
+    fn traveler_location() {
+        let (avatar, characters) = traveler();
+        let localizations = fixture::<Localizations>("loc.json");
+        let (good, unmapped) = Exporter::new(&characters, &localizations)
+            .avatars([&avatar])
+            .finish();
+        assert_eq!(unmapped, []);
+        assert_eq!(good.characters[0].key, "TravelerAnemo");
+        assert_eq!(good.weapons[0].location, "Traveler");
+        assert!(good.artifacts.iter().all(|v| v.location == "Traveler"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let avatar = fixture::<AvatarInfo>("avatar.json");
+        let characters = fixture::<Characters>("characters.json");
+        let localizations = fixture::<Localizations>("loc.json");
+        let (good, _) = Exporter::new(&characters, &localizations)
+            .avatars([&avatar])
+            .finish();
+        let good = serde_json::from_str::<Good>(&serde_json::to_string(&good).unwrap()).unwrap();
+
+        let importer = Importer::new(&characters, &localizations).avatars([&avatar]);
+        assert_eq!(importer.avatar_id("HuTao"), Some(avatar.avatar_id));
+        assert_eq!(importer.weapon_id("StaffOfHoma"), Some(13501));
+        assert_eq!(importer.set_id("CrimsonWitchOfFlames"), Some(15006));
+        assert_eq!(
+            importer
+                .text_map_hash("StaffOfHoma")
+                .map(ToString::to_string),
+            Some("3421967235".to_owned())
+        );
+        let (loadouts, unmapped) = importer.import(&good);
+        assert_eq!(unmapped, []);
+        assert_eq!(
+            loadouts,
+            [Loadout::from_avatar(&avatar, &characters, &localizations).unwrap()]
+        );
+
+        let (loadouts, unmapped) = Importer::new(&characters, &localizations).import(&good);
+        assert_eq!(
+            unmapped,
+            [
+                Unmapped::Key("StaffOfHoma".to_owned()),
+                Unmapped::Key("CrimsonWitchOfFlames".to_owned())
+            ]
+        );
+        assert_eq!(loadouts[0].weapon.as_ref().unwrap().item_id, None);
+    }
+
+    #[test]
+    fn import_traveler() {
+        let (avatar, characters) = traveler();
+        let localizations = fixture::<Localizations>("loc.json");
+        let (mut good, _) = Exporter::new(&characters, &localizations)
+            .avatars([&avatar])
+            .finish();
+        let (loadouts, unmapped) = Importer::new(&characters, &localizations)
+            .avatars([&avatar])
+            .import(&good);
+        assert_eq!(unmapped, []);
+        assert_eq!(loadouts[0].key, "TravelerAnemo");
+        assert_eq!(loadouts[0].weapon.as_ref().unwrap().item_id, Some(13501));
+        assert_eq!(loadouts[0].artifacts.len(), 5);
+
+        let spare = good.weapons[0].clone();
+        good.weapons.push(spare);
+        let (_, unmapped) = Importer::new(&characters, &localizations).import(&good);
+        assert_eq!(
+            unmapped,
+            [
+                Unmapped::Key("StaffOfHoma".to_owned()),
+                Unmapped::Key("CrimsonWitchOfFlames".to_owned())
+            ]
+        );
+    }
+}
@@ -0,0 +1,465 @@
+//! Flattening of showcases into one row per character, for spreadsheets
+//!
+//! Names are resolved through the [`Characters`] and [`Localizations`] stores, falling back to
+//! IDs when missing.
+use crate::gi::{
+    api::{AvatarInfo, EquipType, FightProp, player::info::Info},
+    calc::Stats,
+    store::{Characters, Localizations},
+};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    Uid,
+    Nickname,
+    AdventureRank,
+    WorldLevel,
+    Achievements,
+    SpiralAbyss,
+    ImaginariumTheater,
+    Character,
+    Level,
+    Ascension,
+    Constellation,
+    /// Effective levels, e.g. `9/10/13`
+    Talents,
+    Weapon,
+    WeaponLevel,
+    Refinement,
+    /// Sets with at least 2 pieces, e.g. `Crimson Witch of Flames (4)`
+    ArtifactSets,
+    SandsMainStat,
+    GobletMainStat,
+    CircletMainStat,
+    /// Twice the crit rate plus the crit damage of the artifact substats
+    CritValue,
+    Hp,
+    Atk,
+    Def,
+    ElementalMastery,
+    EnergyRecharge,
+    CritRate,
+    CritDamage,
+    /// Highest elemental or physical DMG bonus
+    DamageBonus,
+}
+
+impl Column {
+    /// Every column, in this order
+    pub const ALL: [Column; 28] = [
+        Column::Uid,
+        Column::Nickname,
+        Column::AdventureRank,
+        Column::WorldLevel,
+        Column::Achievements,
+        Column::SpiralAbyss,
+        Column::ImaginariumTheater,
+        Column::Character,
+        Column::Level,
+        Column::Ascension,
+        Column::Constellation,
+        Column::Talents,
+        Column::Weapon,
+        Column::WeaponLevel,
+        Column::Refinement,
+        Column::ArtifactSets,
+        Column::SandsMainStat,
+        Column::GobletMainStat,
+        Column::CircletMainStat,
+        Column::CritValue,
+        Column::Hp,
+        Column::Atk,
+        Column::Def,
+        Column::ElementalMastery,
+        Column::EnergyRecharge,
+        Column::CritRate,
+        Column::CritDamage,
+        Column::DamageBonus,
+    ];
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Uid => "UID",
+            Column::Nickname => "Nickname",
+            Column::AdventureRank => "AR",
+            Column::WorldLevel => "WL",
+            Column::Achievements => "Achievements",
+            Column::SpiralAbyss => "Spiral Abyss",
+            Column::ImaginariumTheater => "Imaginarium Theater",
+            Column::Character => "Character",
+            Column::Level => "Level",
+            Column::Ascension => "Ascension",
+            Column::Constellation => "Constellation",
+            Column::Talents => "Talents",
+            Column::Weapon => "Weapon",
+            Column::WeaponLevel => "Weapon Level",
+            Column::Refinement => "Refinement",
+            Column::ArtifactSets => "Artifact Sets",
+            Column::SandsMainStat => "Sands",
+            Column::GobletMainStat => "Goblet",
+            Column::CircletMainStat => "Circlet",
+            Column::CritValue => "CV",
+            Column::Hp => "HP",
+            Column::Atk => "ATK",
+            Column::Def => "DEF",
+            Column::ElementalMastery => "EM",
+            Column::EnergyRecharge => "ER%",
+            Column::CritRate => "Crit Rate%",
+            Column::CritDamage => "Crit DMG%",
+            Column::DamageBonus => "DMG Bonus%",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl std::fmt::Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cell::Text(v) => f.write_str(v),
+            Cell::Number(v) => write!(f, "{v}"),
+            Cell::Empty => Ok(()),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(v: String) -> Self {
+        Cell::Text(v)
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Cell::Empty, Into::into)
+    }
+}
+
+impl From<u8> for Cell {
+    fn from(v: u8) -> Self {
+        Cell::Number(v.into())
+    }
+}
+
+impl From<u64> for Cell {
+    fn from(v: u64) -> Self {
+        Cell::Number(v as f64)
+    }
+}
+
+/// Rows of characters, possibly from several players, with the chosen columns
+#[derive(Debug, Clone)]
+pub struct Table<'a> {
+    columns: Vec<Column>,
+    characters: &'a Characters,
+    localizations: &'a Localizations,
+    /// Language of the names, `en` by default
+    pub language: String,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl<'a> Table<'a> {
+    pub fn new(
+        columns: impl Into<Vec<Column>>,
+        characters: &'a Characters,
+        localizations: &'a Localizations,
+    ) -> Self {
+        Table {
+            columns: columns.into(),
+            characters,
+            localizations,
+            language: "en".to_owned(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn rows(&self) -> &[Vec<Cell>] {
+        &self.rows
+    }
+
+    /// Adds a row for every character of a player, e.g. from [`crate::gi::get_player`]
+    pub fn push_player(&mut self, info: &Info, avatars: &[AvatarInfo]) {
+        for avatar in avatars {
+            self.push(Some(info), avatar);
+        }
+    }
+
+    /// Adds a row for a character of unknown owner, e.g. from a build, player columns being left
+    /// empty
+    pub fn push_avatar(&mut self, avatar: &AvatarInfo) {
+        self.push(None, avatar);
+    }
+
+    fn push(&mut self, info: Option<&Info>, avatar: &AvatarInfo) {
+        let stats = Stats::from_avatar(avatar);
+        let row = self
+            .columns
+            .iter()
+            .map(|column| self.cell(*column, info, avatar, &stats))
+            .collect();
+        self.rows.push(row);
+    }
+
+    fn name(&self, hash: impl std::fmt::Display) -> String {
+        self.localizations
+            .get(&self.language, &hash)
+            .map_or_else(|| hash.to_string(), str::to_owned)
+    }
+
+    fn cell(
+        &self,
+        column: Column,
+        info: Option<&Info>,
+        avatar: &AvatarInfo,
+        stats: &Stats,
+    ) -> Cell {
+        let player = info.map(|v| &v.player_info);
+        let weapon = avatar.weapon();
+        let artifacts = avatar.artifacts();
+        let main_stat = |slot: EquipType| {
+            artifacts
+                .clone()
+                .find(|v| v.flat.equip_type == slot)
+                .and_then(|v| v.flat.reliquary_mainstat.prop())
+                .map(|v| v.label().to_owned())
+        };
+        let percent = |prop: FightProp| Cell::Number(round(stats.get(prop) * 100.0, 1));
+        match column {
+            Column::Uid => info.map(|v| v.uid.to_string()).into(),
+            Column::Nickname => player.map(|v| v.nickname.clone()).into(),
+            Column::AdventureRank => player.map(|v| v.level).into(),
+            Column::WorldLevel => player.and_then(|v| v.world_level).into(),
+            Column::Achievements => player.map(|v| v.finish_achievement_num).into(),
+            Column::SpiralAbyss => player
+                .and_then(|v| v.spiral_abyss())
+                .map(|v| v.to_string())
+                .into(),
+            Column::ImaginariumTheater => player
+                .and_then(|v| v.imaginarium_theater())
+                .map(|v| v.to_string())
+                .into(),
+            Column::Character => self
+                .characters
+                .get(avatar.avatar_id, avatar.skill_depot_id)
+                .and_then(|v| v.name_text_map_hash)
+                .map_or_else(|| avatar.avatar_id.to_string(), |v| self.name(v))
+                .into(),
+            Column::Level => avatar.level().into(),
+            Column::Ascension => avatar.ascension().into(),
+            Column::Constellation => avatar.constellation().into(),
+            Column::Talents => avatar
+                .talents(self.characters)
+                .map(|v| v.to_string())
+                .into(),
+            Column::Weapon => weapon.map(|v| self.name(&v.flat.name_text_map_hash)).into(),
+            Column::WeaponLevel => weapon.map(|v| v.weapon.level).into(),
+            Column::Refinement => weapon.map(|v| v.refinement()).into(),
+            Column::ArtifactSets => {
+                let mut sets = BTreeMap::<String, u8>::new();
+                for artifact in artifacts {
+                    *sets
+                        .entry(self.name(&artifact.flat.set_name_text_map_hash))
+                        .or_default() += 1;
+                }
+                let sets = sets
+                    .into_iter()
+                    .filter(|v| v.1 >= 2)
+                    .map(|(name, count)| format!("{name} ({count})"))
+                    .collect::<Vec<_>>();
+                (!sets.is_empty()).then(|| sets.join(" / ")).into()
+            }
+            Column::SandsMainStat => main_stat(EquipType::EquipShoes).into(),
+            Column::GobletMainStat => main_stat(EquipType::EquipRing).into(),
+            Column::CircletMainStat => main_stat(EquipType::EquipDress).into(),
+            Column::CritValue => Cell::Number(round(
+                artifacts
+                    .flat_map(|v| v.flat.reliquary_substats.iter().flatten())
+                    .map(|v| match v.prop() {
+                        Some(FightProp::Critical) => 2.0 * v.stat_value,
+                        Some(FightProp::CriticalHurt) => v.stat_value,
+                        _ => 0.0,
+                    })
+                    .sum(),
+                1,
+            )),
+            Column::Hp => Cell::Number(round(stats.hp(), 0)),
+            Column::Atk => Cell::Number(round(stats.atk(), 0)),
+            Column::Def => Cell::Number(round(stats.def(), 0)),
+            Column::ElementalMastery => {
+                Cell::Number(round(stats.get(FightProp::ElementMastery), 0))
+            }
+            Column::EnergyRecharge => percent(FightProp::ChargeEfficiency),
+            Column::CritRate => percent(FightProp::Critical),
+            Column::CritDamage => percent(FightProp::CriticalHurt),
+            Column::DamageBonus => [
+                FightProp::PhysicalAddHurt,
+                FightProp::FireAddHurt,
+                FightProp::ElecAddHurt,
+                FightProp::WaterAddHurt,
+                FightProp::GrassAddHurt,
+                FightProp::WindAddHurt,
+                FightProp::RockAddHurt,
+                FightProp::IceAddHurt,
+            ]
+            .into_iter()
+            .max_by(|a, b| stats.get(*a).total_cmp(&stats.get(*b)))
+            .map_or(Cell::Empty, percent),
+        }
+    }
+
+    /// Writes a header row followed by every row
+    pub fn write_csv(&self, writer: impl std::io::Write) -> Result<(), csv::Error> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(self.columns.iter().map(Column::header))?;
+        for row in &self.rows {
+            writer.write_record(row.iter().map(ToString::to_string))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Workbook with a single sheet holding a header row followed by every row
+    #[cfg(feature = "xlsx")]
+    pub fn to_xlsx(&self) -> Result<rust_xlsxwriter::Workbook, rust_xlsxwriter::XlsxError> {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let sheet = workbook.add_worksheet();
+        let bold = rust_xlsxwriter::Format::new().set_bold();
+        for (col, column) in (0..).zip(&self.columns) {
+            sheet.write_string_with_format(0, col, column.header(), &bold)?;
+        }
+        for (row, cells) in (1..).zip(&self.rows) {
+            for (col, cell) in (0..).zip(cells) {
+                match cell {
+                    Cell::Text(v) => sheet.write_string(row, col, v)?,
+                    Cell::Number(v) => sheet.write_number(row, col, *v)?,
+                    Cell::Empty => sheet,
+                };
+            }
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        Ok(workbook)
+    }
+}
+
+fn round(v: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (v * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gi::api::{Equip, TextMapHash, player, tests::fixture};
+
+    fn stores() -> (Characters, Localizations) {
+        (fixture("characters.json"), fixture("loc.json"))
+    }
+
+    #[test]
+    fn columns() {
+        let (characters, localizations) = stores();
+        let response = fixture::<player::Response>("player.json");
+        let avatars = response.avatar_info_list.unwrap();
+        let mut table = Table::new(
+            [Column::Character, Column::Uid, Column::Talents],
+            &characters,
+            &localizations,
+        );
+        table.push_player(&response.info, &avatars);
+        table.push_avatar(&avatars[0]);
+        assert_eq!(
+            table.columns(),
+            [Column::Character, Column::Uid, Column::Talents]
+        );
+        let row = |uid: Cell| {
+            vec![
+                Cell::Text("Hu Tao".to_owned()),
+                uid,
+                Cell::Text("9/13/10".to_owned()),
+            ]
+        };
+        assert_eq!(
+            table.rows(),
+            [row(Cell::Text("618285856".to_owned())), row(Cell::Empty)]
+        );
+    }
+
+    #[test]
+    fn crit_value() {
+        let (characters, localizations) = stores();
+        let mut table = Table::new([Column::CritValue], &characters, &localizations);
+        table.push_avatar(&fixture("avatar.json"));
+        // 2 × 28.0% crit rate + 102.6% crit damage, the main stat of the circlet left out
+        assert_eq!(table.rows(), [vec![Cell::Number(158.6)]]);
+    }
+
+    #[test]
+    fn artifact_sets() {
+        let (characters, localizations) = stores();
+        let mut avatar = fixture::<AvatarInfo>("avatar.json");
+        let mut table = Table::new([Column::ArtifactSets], &characters, &localizations);
+        table.push_avatar(&avatar);
+        let pieces = avatar.equip_list.iter_mut().filter_map(|v| match v {
+            Equip::Reliquary(v) => Some(&mut v.flat.set_name_text_map_hash),
+            Equip::Weapon(_) => None,
+        });
+        for (piece, hash) in pieces.zip([1, 1, 2]) {
+            *piece = TextMapHash::U64(hash);
+        }
+        table.push_avatar(&avatar);
+        assert_eq!(
+            table.rows(),
+            [
+                vec![Cell::Text("Crimson Witch of Flames (5)".to_owned())],
+                vec![Cell::Text("1 (2) / Crimson Witch of Flames (2)".to_owned())],
+            ]
+        );
+    }
+
+    #[test]
+    fn csv() {
+        let (characters, localizations) = stores();
+        let response = fixture::<player::Response>("player.json");
+        let mut table = Table::new(
+            [
+                Column::Nickname,
+                Column::Weapon,
+                Column::Refinement,
+                Column::CritRate,
+                Column::GobletMainStat,
+                Column::WorldLevel,
+            ],
+            &characters,
+            &localizations,
+        );
+        table.push_player(&response.info, &response.avatar_info_list.unwrap());
+        let mut csv = Vec::new();
+        table.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "Nickname,Weapon,Refinement,Crit Rate%,Goblet,WL\n\
+            Algoinde,Staff of Homa,1,64.1,Pyro DMG Bonus,9\n"
+        );
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn xlsx() {
+        let (characters, localizations) = stores();
+        let mut table = Table::new(Column::ALL, &characters, &localizations);
+        table.push_avatar(&fixture("avatar.json"));
+        let buffer = table.to_xlsx().unwrap().save_to_buffer().unwrap();
+        // zip container
+        assert!(buffer.starts_with(b"PK\x03\x04"));
+    }
+}
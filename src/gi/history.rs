@@ -0,0 +1,267 @@
+use super::api::{AvatarInfo, PlayerInfo, Uid, player::info::Info};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::{
+    path::Path,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Player data as returned by [`super::get_player`] at a given time
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub taken_at: SystemTime,
+    pub info: Info,
+    pub avatar_info_list: Option<Vec<AvatarInfo>>,
+}
+
+impl Snapshot {
+    /// Snapshot of a [`super::get_player`] result taken now
+    pub fn now((info, avatar_info_list): super::Player) -> Self {
+        Snapshot {
+            taken_at: SystemTime::now(),
+            info,
+            avatar_info_list,
+        }
+    }
+}
+
+/// Storage keeping the history of [`Snapshot`]s of every player
+///
+/// Implementations are expected to skip snapshots identical to the latest one recorded for the
+/// same UID, ignoring [`Info::ttl`] and [`Snapshot::taken_at`].
+pub trait SnapshotStore {
+    type Error;
+
+    /// Records `snapshot`, returning `false` if it was skipped as unchanged
+    fn record(&self, snapshot: &Snapshot) -> Result<bool, Self::Error>;
+
+    /// Every snapshot recorded for `uid`, oldest first
+    fn history(&self, uid: Uid) -> Result<Vec<Snapshot>, Self::Error>;
+
+    fn latest(&self, uid: Uid) -> Result<Option<Snapshot>, Self::Error> {
+        self.history(uid).map(|mut v| v.pop())
+    }
+
+    /// [`PlayerInfo`] of every snapshot recorded for `uid`, oldest first, to follow the evolution
+    /// of fields like [`PlayerInfo::finish_achievement_num`] or the abyss and theater progress
+    fn player_history(&self, uid: Uid) -> Result<Vec<(SystemTime, PlayerInfo)>, Self::Error> {
+        self.history(uid).map(|v| {
+            v.into_iter()
+                .map(|v| (v.taken_at, v.info.player_info))
+                .collect()
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("SQLite query failed")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Failed to (de)serialize snapshot")]
+    Json(#[from] serde_json::Error),
+}
+
+/// [`SnapshotStore`] backed by a SQLite database
+#[derive(Debug)]
+pub struct SqliteStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::new(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    fn new(connection: Connection) -> Result<Self, Error> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS snapshots (
+                id INTEGER PRIMARY KEY,
+                uid TEXT NOT NULL,
+                taken_at INTEGER NOT NULL,
+                info TEXT NOT NULL,
+                avatar_info_list TEXT,
+                content TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS snapshots_uid ON snapshots (uid, taken_at);",
+        )?;
+        Ok(SqliteStore {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+/// Canonical representation of the parts of a snapshot that matter for deduplication, map keys
+/// being sorted by [`serde_json::Value`]
+fn content(snapshot: &Snapshot) -> Result<String, serde_json::Error> {
+    serde_json::to_string(&serde_json::to_value((
+        &snapshot.info.player_info,
+        &snapshot.info.owner,
+        &snapshot.avatar_info_list,
+    ))?)
+}
+
+impl SnapshotStore for SqliteStore {
+    type Error = Error;
+
+    fn record(&self, snapshot: &Snapshot) -> Result<bool, Self::Error> {
+        let uid = snapshot.info.uid.to_string();
+        let content = content(snapshot)?;
+        let connection = self.connection.lock().unwrap();
+        let latest = connection
+            .query_row(
+                "SELECT content FROM snapshots WHERE uid = ?1 ORDER BY taken_at DESC, id DESC LIMIT 1",
+                params![uid],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+        if latest.as_ref() == Some(&content) {
+            return Ok(false);
+        }
+        connection.execute(
+            "INSERT INTO snapshots (uid, taken_at, info, avatar_info_list, content)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                uid,
+                snapshot
+                    .taken_at
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as i64,
+                serde_json::to_string(&snapshot.info)?,
+                snapshot
+                    .avatar_info_list
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                content,
+            ],
+        )?;
+        Ok(true)
+    }
+
+    fn history(&self, uid: Uid) -> Result<Vec<Snapshot>, Self::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT taken_at, info, avatar_info_list FROM snapshots
+            WHERE uid = ?1 ORDER BY taken_at, id",
+        )?;
+        let rows = statement
+            .query_map(params![uid.to_string()], row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter().map(decode).collect()
+    }
+
+    fn latest(&self, uid: Uid) -> Result<Option<Snapshot>, Self::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection
+            .query_row(
+                "SELECT taken_at, info, avatar_info_list FROM snapshots
+                WHERE uid = ?1 ORDER BY taken_at DESC, id DESC LIMIT 1",
+                params![uid.to_string()],
+                row,
+            )
+            .optional()?
+            .map(decode)
+            .transpose()
+    }
+}
+
+type Row = (i64, String, Option<String>);
+
+/// Columns of a snapshot row, left serialized so that the connection can be released before
+/// deserializing them
+fn row(row: &rusqlite::Row) -> rusqlite::Result<Row> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+}
+
+fn decode((taken_at, info, avatar_info_list): Row) -> Result<Snapshot, Error> {
+    Ok(Snapshot {
+        taken_at: UNIX_EPOCH + Duration::from_millis(taken_at as u64),
+        info: serde_json::from_str(&info)?,
+        avatar_info_list: avatar_info_list
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gi::api::{player, tests::fixture};
+
+    fn snapshot(taken_at: u64, achievements: u64) -> Snapshot {
+        let response = fixture::<player::Response>("player.json");
+        let mut info = response.info;
+        info.player_info.finish_achievement_num = achievements;
+        Snapshot {
+            taken_at: UNIX_EPOCH + Duration::from_secs(taken_at),
+            info,
+            avatar_info_list: response.avatar_info_list,
+        }
+    }
+
+    #[test]
+    fn deduplication() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let uid = snapshot(0, 0).info.uid;
+        assert!(store.record(&snapshot(100, 1386)).unwrap());
+        assert!(!store.record(&snapshot(200, 1386)).unwrap());
+        let mut refreshed = snapshot(300, 1386);
+        refreshed.info.ttl = 30;
+        assert!(!store.record(&refreshed).unwrap());
+        assert!(store.record(&snapshot(400, 1387)).unwrap());
+        assert_eq!(store.history(uid).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn latest() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let uid = snapshot(0, 0).info.uid;
+        assert!(store.latest(uid).unwrap().is_none());
+        for (taken_at, achievements) in [(200, 1386), (100, 1385), (200, 1387)] {
+            assert!(store.record(&snapshot(taken_at, achievements)).unwrap());
+        }
+        let latest = store.latest(uid).unwrap().unwrap();
+        assert_eq!(latest.info.player_info.finish_achievement_num, 1387);
+        assert!(latest.avatar_info_list.is_some());
+    }
+
+    #[test]
+    fn oldest_first() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let uid = snapshot(0, 0).info.uid;
+        for (taken_at, achievements) in [(200, 1386), (300, 1387), (100, 1385)] {
+            assert!(store.record(&snapshot(taken_at, achievements)).unwrap());
+        }
+        let history = store.history(uid).unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|v| v.taken_at.duration_since(UNIX_EPOCH).unwrap().as_secs())
+                .collect::<Vec<_>>(),
+            [100, 200, 300]
+        );
+        assert_eq!(
+            store
+                .player_history(uid)
+                .unwrap()
+                .into_iter()
+                .map(|(_, v)| v.finish_achievement_num)
+                .collect::<Vec<_>>(),
+            [1385, 1386, 1387]
+        );
+        assert_eq!(
+            store
+                .latest(uid)
+                .unwrap()
+                .map(|v| v.info.player_info.finish_achievement_num),
+            Some(1387)
+        );
+        assert!(history[0].avatar_info_list.is_some());
+    }
+}
@@ -0,0 +1,80 @@
+//! Game data published by enka.network alongside its API, see
+//! <https://github.com/EnkaNetwork/API-docs/tree/master/store>
+//!
+//! No game data is bundled with the crate: it changes with every game version, and bundling it
+//! would require a new release of the crate for every new character or weapon. Every helper
+//! needing game data takes it from the caller instead, as the types of this module: download the
+//! files once and deserialize them, e.g. with [`serde_json::from_reader`].
+//!
+//! Computing stats and damage needs the game's own tables, not published by enka.network, see
+//! [`excel`].
+pub mod excel;
+
+use super::api::{AvatarId, CostumeId, SkillId, WeaponType};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Contents of `store/loc.json`, text map hashes to text by language code
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct Localizations(pub HashMap<String, HashMap<String, String>>);
+
+impl Localizations {
+    /// Text of `hash` in `language`, e.g. `en`
+    pub fn get(&self, language: &str, hash: impl std::fmt::Display) -> Option<&str> {
+        self.0
+            .get(language)?
+            .get(&hash.to_string())
+            .map(String::as_str)
+    }
+}
+
+/// Contents of `store/characters.json`
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct Characters(pub HashMap<String, Character>);
+
+impl Characters {
+    /// Data of the character `avatar_id` using `skill_depot_id`, the latter only mattering for the
+    /// travelers whose entries are keyed by element
+    pub fn get(&self, avatar_id: AvatarId, skill_depot_id: SkillId) -> Option<&Character> {
+        self.0
+            .get(&format!("{avatar_id}-{skill_depot_id}"))
+            .or_else(|| self.0.get(&avatar_id.to_string()))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Character {
+    pub element: Option<String>,
+    /// Constellation icons, in unlock order
+    #[serde(default)]
+    pub consts: Vec<String>,
+    /// Normal attack, elemental skill and elemental burst
+    #[serde(default)]
+    pub skill_order: Vec<SkillId>,
+    /// Icon of every skill
+    #[serde(default)]
+    pub skills: HashMap<SkillId, String>,
+    /// Proud skill group of every skill, the keys of
+    /// [`super::api::AvatarInfo::proud_skill_extra_level_map`]
+    #[serde(default)]
+    pub proud_map: HashMap<SkillId, u64>,
+    pub name_text_map_hash: Option<u64>,
+    pub side_icon_name: Option<String>,
+    pub quality_type: Option<String>,
+    pub weapon_type: Option<WeaponType>,
+    /// Alternate outfits, keyed by [`super::api::AvatarInfo::costume_id`]
+    #[serde(default)]
+    pub costumes: HashMap<CostumeId, Costume>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Costume {
+    pub side_icon_name: Option<String>,
+    pub icon: Option<String>,
+    /// Splash art, e.g. `UI_Costume_HutaoCostumeButterfly`
+    pub art: Option<String>,
+}
@@ -0,0 +1,347 @@
+//! Tables of the game's `ExcelBinOutput` directory, as published by dataminers, needed to
+//! compute stats and damage
+//!
+//! Only the rows and columns used by [`crate::gi::calc`] and [`crate::gi::damage`] are kept. The
+//! tables are indexed once by [`Excel::from_dir`], which reads the following files:
+//! `AvatarExcelConfigData.json`, `AvatarCurveExcelConfigData.json`,
+//! `AvatarPromoteExcelConfigData.json`, `WeaponExcelConfigData.json`,
+//! `WeaponCurveExcelConfigData.json`, `WeaponPromoteExcelConfigData.json`,
+//! `EquipAffixExcelConfigData.json`, `ReliquarySetExcelConfigData.json`,
+//! `ProudSkillExcelConfigData.json` and `ElementCoeffExcelConfigData.json`.
+use crate::gi::api::{AvatarId, FightProp, ItemId, SkillId};
+use serde::{Deserialize, de::DeserializeOwned};
+use std::{collections::HashMap, path::Path};
+
+/// Stat bonus, e.g. from an ascension or a passive
+///
+/// The game pads some lists with empty entries, deserialized with an empty type.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AddProp {
+    /// e.g. `FIGHT_PROP_CRITICAL_HURT`
+    pub prop_type: String,
+    pub value: f64,
+}
+
+impl AddProp {
+    /// `None` for the props unknown to [`FightProp`]
+    pub fn prop(&self) -> Option<FightProp> {
+        self.prop_type.parse().ok()
+    }
+}
+
+/// Row of `AvatarExcelConfigData.json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Avatar {
+    pub id: AvatarId,
+    #[serde(default)]
+    pub hp_base: f64,
+    #[serde(default)]
+    pub attack_base: f64,
+    #[serde(default)]
+    pub defense_base: f64,
+    #[serde(default)]
+    pub critical: f64,
+    #[serde(default)]
+    pub critical_hurt: f64,
+    #[serde(default)]
+    pub charge_efficiency: f64,
+    /// Curve scaling each base stat with the level
+    #[serde(default)]
+    pub prop_grow_curves: Vec<PropGrowCurve>,
+    /// Key of [`Promote`]
+    #[serde(default)]
+    pub avatar_promote_id: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PropGrowCurve {
+    /// e.g. `FIGHT_PROP_BASE_HP`
+    pub r#type: String,
+    /// e.g. `GROW_CURVE_HP_S5`
+    pub grow_curve: String,
+}
+
+/// Row of `AvatarCurveExcelConfigData.json` and `WeaponCurveExcelConfigData.json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Curve {
+    pub level: u8,
+    #[serde(default)]
+    pub curve_infos: Vec<CurveInfo>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CurveInfo {
+    /// e.g. `GROW_CURVE_HP_S5`
+    pub r#type: String,
+    /// Multiplier of the base stat at [`Curve::level`]
+    pub value: f64,
+}
+
+/// Row of `AvatarPromoteExcelConfigData.json` and `WeaponPromoteExcelConfigData.json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Promote {
+    #[serde(alias = "avatarPromoteId", alias = "weaponPromoteId")]
+    pub promote_id: u64,
+    /// Omitted by the game for the first phase
+    #[serde(default)]
+    pub promote_level: u8,
+    #[serde(default)]
+    pub unlock_max_level: u8,
+    #[serde(default)]
+    pub add_props: Vec<AddProp>,
+}
+
+/// Row of `WeaponExcelConfigData.json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Weapon {
+    pub id: ItemId,
+    #[serde(default)]
+    pub weapon_prop: Vec<WeaponProp>,
+    /// Key of [`Promote`]
+    #[serde(default)]
+    pub weapon_promote_id: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WeaponProp {
+    /// e.g. `FIGHT_PROP_BASE_ATTACK`, empty for the padding entries
+    pub prop_type: String,
+    pub init_value: f64,
+    /// Curve scaling [`Self::init_value`] with the level, e.g. `GROW_CURVE_ATTACK_304`
+    pub r#type: String,
+}
+
+/// Row of `EquipAffixExcelConfigData.json`, the passive of a weapon or artifact set at a given
+/// refinement rank or number of pieces
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EquipAffix {
+    /// Key of [`crate::gi::api::Weapon::affix_map`] and of [`ReliquarySet::equip_affix_id`]
+    pub id: u64,
+    /// Refinement rank starting at 0, or index in [`ReliquarySet::set_need_num`]
+    #[serde(default)]
+    pub level: u8,
+    /// Static bonuses, conditional ones being only described by [`Self::param_list`]
+    #[serde(default)]
+    pub add_props: Vec<AddProp>,
+    #[serde(default)]
+    pub param_list: Vec<f64>,
+}
+
+/// Row of `ReliquarySetExcelConfigData.json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReliquarySet {
+    pub set_id: u64,
+    /// Pieces needed for every bonus, e.g. `[2, 4]`
+    #[serde(default)]
+    pub set_need_num: Vec<u8>,
+    /// Key of [`EquipAffix`]
+    #[serde(rename = "EquipAffixId", alias = "equipAffixId", default)]
+    pub equip_affix_id: u64,
+}
+
+/// Row of `ProudSkillExcelConfigData.json`, a talent or passive at a given level
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProudSkill {
+    /// Key of [`crate::gi::api::AvatarInfo::inherent_proud_skill_list`]
+    pub proud_skill_id: SkillId,
+    /// Value of [`super::Character::proud_map`]
+    pub proud_skill_group_id: u64,
+    #[serde(default)]
+    pub level: u8,
+    /// Static bonuses of passives
+    #[serde(default)]
+    pub add_props: Vec<AddProp>,
+    /// Talent multipliers, in the order of the in-game talent description
+    #[serde(default)]
+    pub param_list: Vec<f64>,
+}
+
+/// Row of `ElementCoeffExcelConfigData.json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementCoeff {
+    pub level: u8,
+    /// Reaction base damage of a character at [`Self::level`]
+    #[serde(default)]
+    pub player_element_level_co: f64,
+}
+
+/// Indexed tables, see the module documentation
+#[derive(Debug, Clone, Default)]
+pub struct Excel {
+    avatars: HashMap<AvatarId, Avatar>,
+    avatar_curves: HashMap<u8, HashMap<String, f64>>,
+    avatar_promotes: HashMap<(u64, u8), Promote>,
+    weapons: HashMap<ItemId, Weapon>,
+    weapon_curves: HashMap<u8, HashMap<String, f64>>,
+    weapon_promotes: HashMap<(u64, u8), Promote>,
+    equip_affixes: HashMap<(u64, u8), EquipAffix>,
+    reliquary_sets: HashMap<u64, ReliquarySet>,
+    proud_skills: HashMap<SkillId, ProudSkill>,
+    proud_skill_levels: HashMap<(u64, u8), SkillId>,
+    element_coeffs: HashMap<u8, f64>,
+}
+
+impl Excel {
+    /// Reads and indexes the tables from `dir`, usually `ExcelBinOutput`
+    pub fn from_dir(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let dir = dir.as_ref();
+        let curves = |name| -> std::io::Result<_> {
+            Ok(read::<Curve>(dir, name)?
+                .into_iter()
+                .map(|v| {
+                    let values = v.curve_infos.into_iter().map(|v| (v.r#type, v.value));
+                    (v.level, values.collect())
+                })
+                .collect())
+        };
+        let promotes = |name| -> std::io::Result<_> {
+            Ok(read::<Promote>(dir, name)?
+                .into_iter()
+                .map(|v| ((v.promote_id, v.promote_level), v))
+                .collect())
+        };
+        let proud_skills = read::<ProudSkill>(dir, "ProudSkillExcelConfigData")?;
+        Ok(Excel {
+            avatars: read::<Avatar>(dir, "AvatarExcelConfigData")?
+                .into_iter()
+                .map(|v| (v.id, v))
+                .collect(),
+            avatar_curves: curves("AvatarCurveExcelConfigData")?,
+            avatar_promotes: promotes("AvatarPromoteExcelConfigData")?,
+            weapons: read::<Weapon>(dir, "WeaponExcelConfigData")?
+                .into_iter()
+                .map(|v| (v.id, v))
+                .collect(),
+            weapon_curves: curves("WeaponCurveExcelConfigData")?,
+            weapon_promotes: promotes("WeaponPromoteExcelConfigData")?,
+            equip_affixes: read::<EquipAffix>(dir, "EquipAffixExcelConfigData")?
+                .into_iter()
+                .map(|v| ((v.id, v.level), v))
+                .collect(),
+            reliquary_sets: read::<ReliquarySet>(dir, "ReliquarySetExcelConfigData")?
+                .into_iter()
+                .map(|v| (v.set_id, v))
+                .collect(),
+            proud_skill_levels: proud_skills
+                .iter()
+                .map(|v| ((v.proud_skill_group_id, v.level), v.proud_skill_id))
+                .collect(),
+            proud_skills: proud_skills
+                .into_iter()
+                .map(|v| (v.proud_skill_id, v))
+                .collect(),
+            element_coeffs: read::<ElementCoeff>(dir, "ElementCoeffExcelConfigData")?
+                .into_iter()
+                .map(|v| (v.level, v.player_element_level_co))
+                .collect(),
+        })
+    }
+
+    pub fn avatar(&self, id: AvatarId) -> Option<&Avatar> {
+        self.avatars.get(&id)
+    }
+
+    /// Multiplier of `curve` at `level`
+    pub fn avatar_curve(&self, curve: &str, level: u8) -> Option<f64> {
+        self.avatar_curves.get(&level)?.get(curve).copied()
+    }
+
+    /// Bonuses of the ascension phase `promote_level`
+    pub fn avatar_promote(&self, promote_id: u64, promote_level: u8) -> Option<&Promote> {
+        self.avatar_promotes.get(&(promote_id, promote_level))
+    }
+
+    pub fn weapon(&self, id: ItemId) -> Option<&Weapon> {
+        self.weapons.get(&id)
+    }
+
+    /// Multiplier of `curve` at `level`
+    pub fn weapon_curve(&self, curve: &str, level: u8) -> Option<f64> {
+        self.weapon_curves.get(&level)?.get(curve).copied()
+    }
+
+    /// Bonuses of the ascension phase `promote_level`
+    pub fn weapon_promote(&self, promote_id: u64, promote_level: u8) -> Option<&Promote> {
+        self.weapon_promotes.get(&(promote_id, promote_level))
+    }
+
+    pub fn equip_affix(&self, id: u64, level: u8) -> Option<&EquipAffix> {
+        self.equip_affixes.get(&(id, level))
+    }
+
+    pub fn reliquary_set(&self, set_id: u64) -> Option<&ReliquarySet> {
+        self.reliquary_sets.get(&set_id)
+    }
+
+    pub fn proud_skill(&self, id: SkillId) -> Option<&ProudSkill> {
+        self.proud_skills.get(&id)
+    }
+
+    /// Row of the proud skill group `group` at `level`, see [`super::Character::proud_map`]
+    pub fn proud_skill_level(&self, group: u64, level: u8) -> Option<&ProudSkill> {
+        self.proud_skill(*self.proud_skill_levels.get(&(group, level))?)
+    }
+
+    /// Reaction base damage of a character at `level`
+    pub fn level_multiplier(&self, level: u8) -> Option<f64> {
+        self.element_coeffs.get(&level).copied()
+    }
+}
+
+fn read<T: DeserializeOwned>(dir: &Path, name: &str) -> std::io::Result<Vec<T>> {
+    let path = dir.join(name).with_extension("json");
+    let file = std::io::BufReader::new(std::fs::File::open(&path)?);
+    serde_json::from_reader(file)
+        .map_err(|e| std::io::Error::other(format!("{}: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_dir() {
+        let excel =
+            Excel::from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/excel")).unwrap();
+        let avatar = excel.avatar(AvatarId(10000046)).unwrap();
+        assert_eq!(avatar.avatar_promote_id, 46);
+        assert_eq!(
+            excel.avatar_curve(&avatar.prop_grow_curves[0].grow_curve, 90),
+            Some(8.0)
+        );
+        let promote = excel.avatar_promote(46, 6).unwrap();
+        assert_eq!(promote.unlock_max_level, 90);
+        assert_eq!(
+            excel.avatar_promote(46, 0).unwrap().add_props[0].prop(),
+            Some(FightProp::BaseHp)
+        );
+        let weapon = excel.weapon(13501).unwrap();
+        assert_eq!(weapon.weapon_prop.len(), 2);
+        assert_eq!(excel.weapon_curve("GROW_CURVE_ATTACK_304", 90), Some(10.0));
+        let set = excel.reliquary_set(15006).unwrap();
+        assert_eq!(set.set_need_num, [2, 4]);
+        let affix = excel.equip_affix(set.equip_affix_id, 0).unwrap();
+        assert_eq!(affix.add_props[0].prop(), Some(FightProp::FireAddHurt));
+        assert!(
+            excel.equip_affix(set.equip_affix_id, 1).unwrap().add_props[0]
+                .prop()
+                .is_none()
+        );
+        assert_eq!(
+            excel.proud_skill(462101).unwrap().proud_skill_group_id,
+            4621
+        );
+    }
+}
@@ -0,0 +1,67 @@
+#![cfg_attr(not(all(feature = "gi", feature = "stateful")), allow(dead_code))]
+use std::{collections::HashMap, sync::Mutex};
+use tokio::sync::watch;
+
+/// Registry of requests currently in flight, keyed by endpoint path, letting concurrent callers
+/// share the outcome of a single request
+#[derive(Debug)]
+pub(crate) struct Inflight<T> {
+    requests: Mutex<HashMap<String, watch::Receiver<Option<T>>>>,
+}
+
+pub(crate) enum Slot<'a, T> {
+    /// No identical request is in flight, the caller has to perform it and report its outcome
+    Leader(Leader<'a, T>),
+    /// An identical request is in flight, the caller can wait for its outcome
+    Follower(watch::Receiver<Option<T>>),
+}
+
+impl<T: Clone> Inflight<T> {
+    pub(crate) fn new() -> Self {
+        Inflight {
+            requests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn join(&self, key: &str) -> Slot<'_, T> {
+        let mut requests = self.requests.lock().unwrap();
+        if let Some(rx) = requests.get(key) {
+            return Slot::Follower(rx.clone());
+        }
+        let (tx, rx) = watch::channel(None);
+        requests.insert(key.to_owned(), rx);
+        Slot::Leader(Leader {
+            inflight: self,
+            key: key.to_owned(),
+            tx,
+        })
+    }
+}
+
+/// Waits for the leader of a request to report its outcome, returning `None` if the leader was
+/// dropped before finishing
+pub(crate) async fn follow<T: Clone>(mut rx: watch::Receiver<Option<T>>) -> Option<T> {
+    rx.wait_for(Option::is_some)
+        .await
+        .ok()
+        .and_then(|v| v.clone())
+}
+
+/// Unregisters its request once dropped, whether it finished or was cancelled
+pub(crate) struct Leader<'a, T> {
+    inflight: &'a Inflight<T>,
+    key: String,
+    tx: watch::Sender<Option<T>>,
+}
+
+impl<T> Leader<'_, T> {
+    pub(crate) fn finish(self, value: T) {
+        self.tx.send_replace(Some(value));
+    }
+}
+
+impl<T> Drop for Leader<'_, T> {
+    fn drop(&mut self) {
+        self.inflight.requests.lock().unwrap().remove(&self.key);
+    }
+}
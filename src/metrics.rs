@@ -0,0 +1,137 @@
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Family of enka.network endpoints a request belongs to, used to label [`Metrics`] events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// `api/uid/{uid}`
+    Uid,
+    /// `api/profile/{username}`
+    Profile,
+    /// `api/profile/{username}/hoyos` and `api/profile/{username}/hoyos/{hash}`
+    Hoyos,
+    /// `api/profile/{username}/hoyos/{hash}/builds` and `.../builds/{build_id}`
+    Builds,
+}
+
+impl Endpoint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Endpoint::Uid => "uid",
+            Endpoint::Profile => "profile",
+            Endpoint::Hoyos => "hoyos",
+            Endpoint::Builds => "builds",
+        }
+    }
+}
+
+/// Coarse outcome of a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusClass {
+    /// 1xx
+    Informational,
+    /// 2xx
+    Success,
+    /// 3xx
+    Redirection,
+    /// 4xx
+    ClientError,
+    /// 5xx
+    ServerError,
+    /// The request never got a response, see [`crate::gi::Error::Request`]
+    Transport,
+}
+
+impl StatusClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatusClass::Informational => "1xx",
+            StatusClass::Success => "2xx",
+            StatusClass::Redirection => "3xx",
+            StatusClass::ClientError => "4xx",
+            StatusClass::ServerError => "5xx",
+            StatusClass::Transport => "transport",
+        }
+    }
+}
+
+impl From<StatusCode> for StatusClass {
+    fn from(status: StatusCode) -> Self {
+        match status.as_u16() {
+            100..200 => StatusClass::Informational,
+            200..300 => StatusClass::Success,
+            300..400 => StatusClass::Redirection,
+            400..500 => StatusClass::ClientError,
+            _ => StatusClass::ServerError,
+        }
+    }
+}
+
+/// Observer receiving events about the requests made through an [`crate::EnkaClient`]
+///
+/// Every method has an empty default implementation, so implementors only need to override the
+/// events they care about.
+#[allow(unused_variables)]
+pub trait Metrics: Send + Sync {
+    /// A request is about to be sent
+    fn request_started(&self, endpoint: Endpoint) {}
+    /// A request completed, either with a response or with a transport failure
+    fn request_finished(&self, endpoint: Endpoint, status: StatusClass, elapsed: Duration) {}
+    /// A successful response could not be deserialized into the expected type
+    fn deserialization_failed(&self, endpoint: Endpoint) {}
+    /// A response was served from the cache without contacting enka.network
+    fn cache_hit(&self, endpoint: Endpoint) {}
+    /// No usable cached response was found and a request will be sent
+    fn cache_miss(&self, endpoint: Endpoint) {}
+    /// A request was delayed by the rate limiter
+    fn rate_limit_waited(&self, endpoint: Endpoint, waited: Duration) {}
+}
+
+/// [`Metrics`] implementation forwarding every event to the globally installed [`::metrics`]
+/// recorder
+///
+/// All metrics are labelled with `endpoint`, `enka_requests_total` is also labelled with `status`.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GlobalRecorder;
+
+#[cfg(feature = "metrics")]
+impl Metrics for GlobalRecorder {
+    fn request_started(&self, endpoint: Endpoint) {
+        ::metrics::counter!("enka_requests_started_total", "endpoint" => endpoint.as_str())
+            .increment(1);
+    }
+
+    fn request_finished(&self, endpoint: Endpoint, status: StatusClass, elapsed: Duration) {
+        ::metrics::counter!(
+            "enka_requests_total",
+            "endpoint" => endpoint.as_str(),
+            "status" => status.as_str()
+        )
+        .increment(1);
+        ::metrics::histogram!("enka_request_duration_seconds", "endpoint" => endpoint.as_str())
+            .record(elapsed);
+    }
+
+    fn deserialization_failed(&self, endpoint: Endpoint) {
+        ::metrics::counter!(
+            "enka_deserialization_failures_total",
+            "endpoint" => endpoint.as_str()
+        )
+        .increment(1);
+    }
+
+    fn cache_hit(&self, endpoint: Endpoint) {
+        ::metrics::counter!("enka_cache_hits_total", "endpoint" => endpoint.as_str()).increment(1);
+    }
+
+    fn cache_miss(&self, endpoint: Endpoint) {
+        ::metrics::counter!("enka_cache_misses_total", "endpoint" => endpoint.as_str())
+            .increment(1);
+    }
+
+    fn rate_limit_waited(&self, endpoint: Endpoint, waited: Duration) {
+        ::metrics::histogram!("enka_rate_limit_wait_seconds", "endpoint" => endpoint.as_str())
+            .record(waited);
+    }
+}
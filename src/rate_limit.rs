@@ -0,0 +1,30 @@
+#![cfg_attr(not(all(feature = "gi", feature = "stateful")), allow(dead_code))]
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Spaces requests at least `interval` apart
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(interval: Duration) -> Self {
+        RateLimiter {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Reserves the next request slot, returning how long the caller has to wait before using it
+    pub(crate) fn reserve(&self) -> Duration {
+        let now = Instant::now();
+        let mut next = self.next.lock().unwrap();
+        let slot = (*next).max(now);
+        *next = slot + self.interval;
+        slot - now
+    }
+}
@@ -0,0 +1,18 @@
+//! Zenless Zone Zero, whose endpoints are not modelled yet
+
+/// Zenless Zone Zero handle of an [`crate::EnkaClient`], cheap to clone and to move across tasks
+///
+/// Only shares the configuration of the client for now, so that callers can already hold one next
+/// to `gi::Wrapper`.
+#[cfg(feature = "stateful")]
+#[derive(Debug, Clone)]
+pub struct Wrapper {
+    pub(crate) client: crate::EnkaClient,
+}
+
+#[cfg(feature = "stateful")]
+impl Wrapper {
+    pub fn client(&self) -> &crate::EnkaClient {
+        &self.client
+    }
+}